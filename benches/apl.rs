@@ -1,7 +1,10 @@
 mod util;
 use aava::{
-    algorithms::apl::{self, hyper_ball::hyper_ball},
-    graphs::{csr::CSR, edge_list::EdgeList, Graph},
+    algorithms::apl::{
+        self,
+        hyper_ball::{hyper_ball, par_hyper_ball},
+    },
+    graphs::{csr::CSR, edge_list::EdgeList, transform::Transform, Graph},
     util::hyper_counters::*,
 };
 use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
@@ -14,10 +17,20 @@ pub fn bench(c: &mut Criterion) {
         group.bench_function(BenchmarkId::new("apl", format!("{}_{}_{}", n, p, e)), |b| {
             b.iter_batched(
                 || gen_graph::<EdgeList>(n, p),
-                |mut graph| apl::apl(&mut graph),
+                |graph| apl::apl(&graph),
                 BatchSize::SmallInput,
             )
         });
+        group.bench_function(
+            BenchmarkId::new("sampled", format!("{}_{}_{}", n, p, e)),
+            |b| {
+                b.iter_batched(
+                    || gen_graph::<CSR>(n, p).symmetrize(|a, _| a),
+                    |graph| apl::sampled::apl(&graph, 16, 0.95, make_rng()),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
         group.bench_function(
             BenchmarkId::new("hyperLogLog", format!("{}_{}_{}", n, p, e)),
             |b| {
@@ -42,6 +55,21 @@ pub fn bench(c: &mut Criterion) {
                 )
             },
         );
+        group.bench_function(
+            BenchmarkId::new("par_hyperLogLog", format!("{}_{}_{}", n, p, e)),
+            |b| {
+                b.iter_batched(
+                    || gen_graph::<CSR>(n, p),
+                    |graph| {
+                        par_hyper_ball(
+                            &graph,
+                            vec![HyperLogLog::new(B::B4); graph.vertices()].into_boxed_slice(),
+                        );
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
     }
     group.finish();
 }
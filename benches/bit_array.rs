@@ -9,7 +9,7 @@ pub fn iteration(c: &mut Criterion) {
             let num_max = 1 << w;
             let mut v = BitArray::new(w, ELEMS);
             for n in 0..ELEMS {
-                v.set(n as usize, (n as u8) % num_max);
+                v.set(n, (n as u8) % num_max);
             }
             v
         })
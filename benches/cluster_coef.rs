@@ -1,58 +1,51 @@
 mod util;
 use aava::{
     algorithms::clustering_coef::c_coef,
-    graphs::{csr::CSR, matrix::Adjacency, test_graphs::clustered, FromEdges, Graph},
-    util::ToExactSizeIter,
+    graphs::{csr::CSR, matrix::Adjacency, sorted::Sorted, test_graphs::clustered, Graph},
 };
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use util::*;
 
-pub fn make_params() -> impl Iterator<Item = ((Adjacency, CSR, Vec<usize>), usize, usize)> {
+pub fn make_params() -> impl Iterator<Item = ((Adjacency, CSR), usize, usize)> {
     (1..9)
         .map(|i| i * 500)
         .flat_map(|n| [1, 2, 3, 5, 8, 12, 50].iter().map(move |&o| (n, 10, o)))
         .map(|(n, d, o)| {
             let adj = clustered::<Adjacency, _>(n, d, o, make_rng());
-            let csr = CSR::from_edges(
-                n,
-                adj.neighbourhoods()
-                    .flat_map(|(f, tos)| tos.map(move |t| (f, t.to)))
-                    .to_exact_size(adj.edges()),
-            );
-            let node_indexes = adj
-                .neighbourhoods()
-                .map(|(i, n)| (i, n.count()))
-                .filter(|x| x.1 > 2)
-                .map(|i| i.0)
-                .collect::<Vec<_>>();
-            ((adj, csr, node_indexes), d, o)
+            let csr = CSR::from(adj.clone());
+            ((adj, csr), d, o)
         })
 }
 
 pub fn bench(c: &mut Criterion) {
     let mut group = c.benchmark_group("Clustering Coef");
-    for ((adj, csr, node_indexes), d, o) in make_params() {
+    for ((adj, csr), d, o) in make_params() {
         let n = adj.vertices().pow(2) + adj.edges();
         group.throughput(Throughput::Elements(n as u64));
         group.bench_function(BenchmarkId::new("adj", format!("{}_{}_{}", n, d, o)), |b| {
-            b.iter(|| c_coef(20, black_box(&node_indexes), black_box(&adj), make_rng()))
+            b.iter(|| c_coef(20, black_box(&adj), make_rng()))
         });
         group.bench_function(BenchmarkId::new("csr", format!("{}_{}_{}", n, d, o)), |b| {
-            b.iter(|| c_coef(20, black_box(&node_indexes), black_box(&csr), make_rng()))
+            b.iter(|| c_coef(20, black_box(&csr), make_rng()))
         });
+        let sorted = Sorted::new(csr);
+        group.bench_function(
+            BenchmarkId::new("sorted_csr", format!("{}_{}_{}", n, d, o)),
+            |b| b.iter(|| c_coef(20, black_box(&sorted), make_rng())),
+        );
     }
     group.finish();
 }
 
 pub fn apl(c: &mut Criterion) {
     let mut group = c.benchmark_group("Clustering Coef Adj Only");
-    for ((adj, _, node_indexes), d, o) in make_params() {
+    for ((adj, _), d, o) in make_params() {
         let n = adj.vertices() + adj.edges();
         group.throughput(Throughput::Elements(n as u64));
         group.bench_with_input(
             BenchmarkId::from_parameter(format!("{}_{}_{}", n, d, o)),
-            &(node_indexes, adj),
-            |b, (n, g)| b.iter(|| c_coef(20, n, g, make_rng())),
+            &adj,
+            |b, g| b.iter(|| c_coef(20, g, make_rng())),
         );
     }
     group.finish();
@@ -60,13 +53,13 @@ pub fn apl(c: &mut Criterion) {
 
 pub fn csr(c: &mut Criterion) {
     let mut group = c.benchmark_group("Clustering Coef CSR Only");
-    for ((_, csr, node_indexes), d, o) in make_params() {
+    for ((_, csr), d, o) in make_params() {
         let n = csr.vertices() + csr.edges();
         group.throughput(Throughput::Elements(n as u64));
         group.bench_with_input(
             BenchmarkId::from_parameter(format!("{}_{}_{}", n, d, o)),
-            &(node_indexes, csr),
-            |b, (n, g)| b.iter(|| c_coef(20, n, g, make_rng())),
+            &csr,
+            |b, g| b.iter(|| c_coef(20, g, make_rng())),
         );
     }
     group.finish();
@@ -1,6 +1,10 @@
 mod util;
 use aava::graphs::{
-    csr::CSR, edge_list::EdgeList, matrix::Adjacency, test_graphs::random_graph_er, FromEdges,
+    csr::CSR,
+    edge_list::EdgeList,
+    matrix::Adjacency,
+    test_graphs::{random_graph, random_graph_er},
+    FromEdges, WFromEdges,
 };
 use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
 use util::*;
@@ -16,7 +20,7 @@ macro_rules! bench_graph {
             |b| {
                 b.iter_batched(
                     || gen_edges($n, $p),
-                    |(nn, edges)| <$t>::from_edges(nn, edges),
+                    |(nn, edges)| <$t as FromEdges>::from_edges(nn, edges),
                     BatchSize::SmallInput,
                 )
             },
@@ -35,9 +39,51 @@ pub fn make_graphs(c: &mut Criterion) {
     group.finish();
 }
 
+pub fn make_csrs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("CsrCreation");
+    for &(n, m) in &[
+        (10_000, 100_000),
+        (100_000, 1_000_000),
+        (1_000_000, 10_000_000),
+    ] {
+        let edges = random_graph(n, m, make_rng())
+            .into_iter()
+            .map(|(f, t)| (f, t, ()))
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(m as u64));
+        let id = |name| BenchmarkId::new(name, format!("{}_{}", n, m));
+        group.bench_function(id("from_edges"), |b| {
+            b.iter_batched(
+                || edges.clone(),
+                |edges| <CSR as WFromEdges>::from_edges(n, edges),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(id("par_from_edges"), |b| {
+            b.iter_batched(
+                || edges.clone(),
+                |edges| CSR::<()>::par_from_edges(n, edges),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(id("par_from_edges_sorted"), |b| {
+            b.iter_batched(
+                || edges.clone(),
+                |edges| {
+                    let mut g = CSR::<()>::par_from_edges(n, edges);
+                    g.par_sort_neighbours();
+                    g
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(10);
-    targets = make_graphs
+    targets = make_graphs, make_csrs
 }
 criterion_main!(benches);
@@ -1,3 +1,5 @@
 pub mod min_cut;
 pub mod apl;
 pub mod clustering_coef;
+pub mod components;
+pub mod shortest_paths;
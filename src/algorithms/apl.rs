@@ -1,17 +1,157 @@
+pub mod bfs;
 pub mod hyper_ball;
-use crate::graphs::*;
-pub fn apl<G>(g: &G) -> f64
+pub mod sampled;
+pub mod weighted;
+use crate::{
+    algorithms::components::{self, Components},
+    graphs::*,
+};
+use std::fmt::{self, Display};
+
+/// What to do with the pairs of vertices that have no path between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unreachable {
+    /// Average only the pairs that are connected.
+    Skip,
+    /// Use the harmonic mean of all distances, unreachable pairs being infinitely far apart.
+    Harmonic,
+    /// Average each weakly connected component on its own.
+    PerComponent,
+    /// Fail if any pair is unreachable.
+    Error,
+}
+
+/// The average path length of a single component.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComponentApl {
+    /// The smallest vertex of the component.
+    pub component: usize,
+    pub vertices: usize,
+    pub apl: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AplValue {
+    Mean(f64),
+    Harmonic(f64),
+    PerComponent(Vec<ComponentApl>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AplReport {
+    pub value: AplValue,
+    /// Ordered pairs of distinct vertices `(u, v)` such that `v` is reachable from `u`.
+    pub connected_pairs: usize,
+    /// Ordered pairs of distinct vertices `(u, v)` such that `v` is not reachable from `u`.
+    pub unreachable_pairs: usize,
+}
+
+/// Returned by [`Unreachable::Error`] with the number of unreachable pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnreachablePairs(pub usize);
+
+impl Display for UnreachablePairs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} pairs of vertices are not connected", self.0)
+    }
+}
+
+impl std::error::Error for UnreachablePairs {}
+
+/// The distances from a single vertex to every other vertex it reaches.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SourceDistances {
+    /// `Σ d(v, w)`
+    pub total: f64,
+    /// `|{w | w != v, d(v, w) < ∞}|`
+    pub reached: f64,
+    /// `Σ 1 / d(v, w)`
+    pub harmonic: f64,
+}
+
+fn mean<'a, I: IntoIterator<Item = &'a SourceDistances>>(sources: I) -> f64 {
+    let (total, reached) = sources
+        .into_iter()
+        .fold((0.0, 0.0), |(t, r), s| (t + s.total, r + s.reached));
+    if reached > 0.0 {
+        total / reached
+    } else {
+        0.0
+    }
+}
+
+/// Applies `policy` to the distances from every vertex. `components` finds the weakly connected
+/// components of the graph and is only called for [`Unreachable::PerComponent`].
+pub(crate) fn report<C>(
+    sources: &[SourceDistances],
+    components: C,
+    policy: Unreachable,
+) -> Result<AplReport, UnreachablePairs>
 where
-    G: EdgeListGraph,
+    C: FnOnce() -> Components,
 {
+    let n = sources.len();
+    let all_pairs = n * n.saturating_sub(1);
+    let connected_pairs = sources
+        .iter()
+        .map(|s| s.reached)
+        .sum::<f64>()
+        .round()
+        .max(0.0) as usize;
+    let unreachable_pairs = all_pairs.saturating_sub(connected_pairs);
+    let value = match policy {
+        Unreachable::Error if unreachable_pairs > 0 => {
+            return Err(UnreachablePairs(unreachable_pairs))
+        }
+        Unreachable::Skip | Unreachable::Error => AplValue::Mean(mean(sources)),
+        Unreachable::Harmonic => {
+            let inverse = sources.iter().map(|s| s.harmonic).sum::<f64>();
+            AplValue::Harmonic(if inverse > 0.0 {
+                all_pairs as f64 / inverse
+            } else {
+                f64::INFINITY
+            })
+        }
+        Unreachable::PerComponent => {
+            let components = components();
+            let mut smallest = vec![usize::MAX; components.count];
+            let mut by_component = vec![Vec::new(); components.count];
+            for (v, (&c, s)) in components.labels.iter().zip(sources).enumerate() {
+                smallest[c] = smallest[c].min(v);
+                by_component[c].push(s);
+            }
+            let mut per_component = smallest
+                .into_iter()
+                .zip(by_component)
+                .map(|(component, sources)| ComponentApl {
+                    component,
+                    vertices: sources.len(),
+                    apl: mean(sources),
+                })
+                .collect::<Vec<_>>();
+            per_component.sort_unstable_by_key(|c| c.component);
+            AplValue::PerComponent(per_component)
+        }
+    };
+    Ok(AplReport {
+        value,
+        connected_pairs,
+        unreachable_pairs,
+    })
+}
+
+/// Floyd–Warshall over the graph, taking each edge as undirected.
+fn distances<G: Successors>(g: &G) -> Vec<SourceDistances> {
     let n = g.vertices();
     let mut distances = vec![vec![usize::MAX; n]; n];
-    for i in 0..n {
-        distances[i][i] = 0;
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[i] = 0;
     }
-    for p in g.as_edges() {
-        distances[p.0][p.1] = 1;
-        distances[p.1][p.0] = 1;
+    for (from, to) in arcs(g) {
+        if from != to {
+            distances[from][to] = 1;
+            distances[to][from] = 1;
+        }
     }
     for k in 0..n {
         for i in 0..n {
@@ -22,15 +162,116 @@ where
             }
         }
     }
-    distances.iter().flatten().sum::<usize>() as f64 / ((n * (n - 1)) as f64 / 2.0)
+    distances
+        .iter()
+        .map(|row| {
+            row.iter().filter(|&&d| d != 0 && d != usize::MAX).fold(
+                SourceDistances::default(),
+                |s, &d| SourceDistances {
+                    total: s.total + d as f64,
+                    reached: s.reached + 1.0,
+                    harmonic: s.harmonic + 1.0 / d as f64,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Every arc of `g`, as `(from, to)` pairs.
+fn arcs<G: Successors>(g: &G) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..g.vertices()).flat_map(move |v| g.successors(v).map(move |w| (v, w)))
+}
+
+/// The average shortest path length between connected pairs of vertices.
+pub fn apl<G>(g: &G) -> f64
+where
+    G: Successors,
+{
+    mean(&distances(g))
+}
+
+/// The average shortest path length, handling unreachable pairs according to `policy`.
+pub fn apl_with<G>(g: &G, policy: Unreachable) -> Result<AplReport, UnreachablePairs>
+where
+    G: Successors,
+{
+    report(&distances(g), || components::weakly_connected(g), policy)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::graphs::{edge_list::EdgeList, test_graphs};
+    use super::*;
+    use crate::graphs::{
+        compressed::CompressedCSR, csr::CSR, edge_list::EdgeList, matrix::Adjacency, test_graphs,
+        transform::Transform,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
     #[test]
     fn apl0() {
         let apl = super::apl(&test_graphs::graph_one::<EdgeList>());
-        approx::assert_abs_diff_eq!(apl, test_graphs::GRAPH_ONE_APL)
+        approx::assert_abs_diff_eq!(apl, test_graphs::GRAPH_ONE_APL);
+        let csr = test_graphs::graph_one::<CSR>();
+        approx::assert_abs_diff_eq!(super::apl(&csr), test_graphs::GRAPH_ONE_APL);
+        let adjacency = test_graphs::graph_one::<Adjacency>();
+        approx::assert_abs_diff_eq!(super::apl(&adjacency), test_graphs::GRAPH_ONE_APL);
+        let compressed = CompressedCSR::from_csr(&csr);
+        approx::assert_abs_diff_eq!(super::apl(&compressed), test_graphs::GRAPH_ONE_APL);
+    }
+
+    #[test]
+    fn disconnected() {
+        // a path 0 - 1 - 2 and an edge 3 - 4
+        let g: EdgeList = crate::graph![EdgeList = (5) { 0 => 1; 1 => 2; 3 => 4 }];
+        approx::assert_abs_diff_eq!(apl(&g), (8.0 + 2.0) / 8.0);
+        let report = |p| apl_with(&g, p);
+        assert_eq!(
+            report(Unreachable::Skip),
+            Ok(AplReport {
+                value: AplValue::Mean((8.0 + 2.0) / 8.0),
+                connected_pairs: 8,
+                unreachable_pairs: 12,
+            })
+        );
+        assert_eq!(report(Unreachable::Error), Err(UnreachablePairs(12)));
+        assert_eq!(
+            report(Unreachable::Harmonic).unwrap().value,
+            AplValue::Harmonic(20.0 / (4.0 + 2.0 * 0.5 + 2.0))
+        );
+        assert_eq!(
+            report(Unreachable::PerComponent).unwrap().value,
+            AplValue::PerComponent(vec![
+                ComponentApl {
+                    component: 0,
+                    vertices: 3,
+                    apl: 8.0 / 6.0
+                },
+                ComponentApl {
+                    component: 3,
+                    vertices: 2,
+                    apl: 1.0
+                },
+            ])
+        );
+        let connected = apl_with(&test_graphs::graph_one::<EdgeList>(), Unreachable::Error);
+        assert_eq!(connected.unwrap().unreachable_pairs, 0);
+    }
+
+    #[test]
+    fn bfs_on_symmetrized() {
+        // the generators store each edge once, which Floyd takes as undirected
+        let edges = test_graphs::random_graph_er(60, 0.05, StdRng::seed_from_u64(0xBAD5EED));
+        let g = <CSR as FromEdges>::from_edges(60, edges);
+        let floyd = apl_with(&g, Unreachable::Skip).unwrap();
+        let symmetric = g.symmetrize(|a, _| a);
+        let lengths = bfs::path_lengths(&symmetric);
+        assert_eq!(floyd.connected_pairs, lengths.pairs);
+        assert_eq!(floyd.value, AplValue::Mean(lengths.apl()));
+        approx::assert_abs_diff_eq!(apl(&g), lengths.apl());
+        assert!(bfs::path_lengths(&g).pairs < lengths.pairs);
+        assert_eq!(bfs::par_path_lengths(&symmetric), lengths);
+        assert_eq!(bfs::bit_parallel_path_lengths(&symmetric), lengths);
+        assert_eq!(bfs::par_bit_parallel_path_lengths(&symmetric), lengths);
+        assert_eq!(bfs::apl_with(&symmetric, Unreachable::Skip), Ok(floyd));
     }
 }
@@ -0,0 +1,285 @@
+//! Exact average path length of unweighted graphs, running a breadth first search from every
+//! vertex. This takes `O(nm)` time but only `O(n)` memory per search, so, unlike
+//! [`apl`](super::apl), it can handle graphs with more than a few thousand vertices.
+//!
+//! Distances follow the arcs of the graph, so an undirected graph has to store both directions
+//! of each edge.
+use super::{report, AplReport, SourceDistances, Unreachable, UnreachablePairs};
+use crate::{algorithms::components, graphs::Successors};
+use rayon::prelude::*;
+use std::{iter::Sum, ops::Add};
+
+/// The lengths of the shortest paths between all pairs of distinct vertices connected by a path.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PathLengths {
+    /// The sum of the lengths of all shortest paths.
+    pub total: usize,
+    /// The number of ordered pairs `(u, v)`, `u != v`, such that `v` is reachable from `u`.
+    pub pairs: usize,
+}
+
+impl PathLengths {
+    pub fn apl(&self) -> f64 {
+        if self.pairs == 0 {
+            0.0
+        } else {
+            self.total as f64 / self.pairs as f64
+        }
+    }
+}
+
+impl Add for PathLengths {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            total: self.total + other.total,
+            pairs: self.pairs + other.pairs,
+        }
+    }
+}
+
+impl Sum for PathLengths {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Default::default(), Add::add)
+    }
+}
+
+/// Reusable buffers for a single source breadth first search.
+pub(super) struct Bfs {
+    distances: Box<[usize]>,
+    queue: Vec<usize>,
+}
+
+impl Bfs {
+    pub(super) fn new(n: usize) -> Self {
+        Self {
+            distances: vec![usize::MAX; n].into_boxed_slice(),
+            queue: Vec::with_capacity(n),
+        }
+    }
+
+    /// Returns the lengths of the paths from `source` and the sum of their inverses.
+    pub(super) fn run<G: Successors>(&mut self, g: &G, source: usize) -> (PathLengths, f64) {
+        let mut lengths = PathLengths::default();
+        let mut harmonic = 0.0;
+        self.queue.clear();
+        self.queue.push(source);
+        self.distances[source] = 0;
+        let mut head = 0;
+        while let Some(&v) = self.queue.get(head) {
+            head += 1;
+            let d = self.distances[v] + 1;
+            for w in g.successors(v) {
+                if self.distances[w] == usize::MAX {
+                    self.distances[w] = d;
+                    lengths.total += d;
+                    lengths.pairs += 1;
+                    harmonic += 1.0 / d as f64;
+                    self.queue.push(w);
+                }
+            }
+        }
+        // Only the visited vertices need to be reset
+        for &v in &self.queue {
+            self.distances[v] = usize::MAX;
+        }
+        (lengths, harmonic)
+    }
+}
+
+/// Runs a breadth first search from every vertex, one at a time.
+pub fn path_lengths<G: Successors>(g: &G) -> PathLengths {
+    let mut bfs = Bfs::new(g.vertices());
+    (0..g.vertices()).map(|s| bfs.run(g, s).0).sum()
+}
+
+/// Runs a breadth first search from every vertex, splitting the sources among the threads of
+/// rayon's thread pool.
+pub fn par_path_lengths<G: Successors + Sync>(g: &G) -> PathLengths {
+    (0..g.vertices())
+        .into_par_iter()
+        .map_init(|| Bfs::new(g.vertices()), |bfs, s| bfs.run(g, s).0)
+        .reduce(PathLengths::default, Add::add)
+}
+
+const BATCH: usize = 64;
+
+/// Buffers for a multi source breadth first search, each bit of a word tracking one source.
+struct MultiSourceBfs {
+    seen: Box<[u64]>,
+    visit: Box<[u64]>,
+    next: Box<[u64]>,
+}
+
+impl MultiSourceBfs {
+    fn new(n: usize) -> Self {
+        Self {
+            seen: vec![0; n].into_boxed_slice(),
+            visit: vec![0; n].into_boxed_slice(),
+            next: vec![0; n].into_boxed_slice(),
+        }
+    }
+
+    /// Searches from up to 64 sources at once, sharing the traversal of the arcs between them.
+    fn run<G: Successors>(&mut self, g: &G, first: usize) -> PathLengths {
+        let mut lengths = PathLengths::default();
+        self.seen.iter_mut().for_each(|s| *s = 0);
+        self.visit.iter_mut().for_each(|s| *s = 0);
+        for (i, s) in (first..usize::min(first + BATCH, g.vertices())).enumerate() {
+            self.seen[s] = 1 << i;
+            self.visit[s] = 1 << i;
+        }
+        let mut distance = 0;
+        let mut active = true;
+        while active {
+            distance += 1;
+            for (v, &visit) in self.visit.iter().enumerate().filter(|(_, &b)| b != 0) {
+                for w in g.successors(v) {
+                    self.next[w] |= visit;
+                }
+            }
+            active = false;
+            for ((seen, visit), next) in self
+                .seen
+                .iter_mut()
+                .zip(self.visit.iter_mut())
+                .zip(self.next.iter_mut())
+            {
+                let new = *next & !*seen;
+                *seen |= new;
+                *visit = new;
+                *next = 0;
+                if new != 0 {
+                    let found = new.count_ones() as usize;
+                    lengths.total += distance * found;
+                    lengths.pairs += found;
+                    active = true;
+                }
+            }
+        }
+        lengths
+    }
+}
+
+/// Runs one multi source breadth first search for each batch of 64 vertices.
+pub fn bit_parallel_path_lengths<G: Successors>(g: &G) -> PathLengths {
+    let mut bfs = MultiSourceBfs::new(g.vertices());
+    (0..g.vertices())
+        .step_by(BATCH)
+        .map(|first| bfs.run(g, first))
+        .sum()
+}
+
+/// Runs the multi source breadth first searches in parallel.
+pub fn par_bit_parallel_path_lengths<G: Successors + Sync>(g: &G) -> PathLengths {
+    let batches = g.vertices().div_ceil(BATCH);
+    (0..batches)
+        .into_par_iter()
+        .map_init(
+            || MultiSourceBfs::new(g.vertices()),
+            |bfs, b| bfs.run(g, b * BATCH),
+        )
+        .reduce(PathLengths::default, Add::add)
+}
+
+/// The exact average path length between connected pairs of vertices.
+pub fn apl<G: Successors + Sync>(g: &G) -> f64 {
+    par_bit_parallel_path_lengths(g).apl()
+}
+
+/// The exact average path length, handling unreachable pairs according to `policy`.
+/// Components are weakly connected, so they ignore the direction of the arcs.
+pub fn apl_with<G: Successors + Sync>(
+    g: &G,
+    policy: Unreachable,
+) -> Result<AplReport, UnreachablePairs> {
+    let sources = (0..g.vertices())
+        .into_par_iter()
+        .map_init(
+            || Bfs::new(g.vertices()),
+            |bfs, s| {
+                let (lengths, harmonic) = bfs.run(g, s);
+                SourceDistances {
+                    total: lengths.total as f64,
+                    reached: lengths.pairs as f64,
+                    harmonic,
+                }
+            },
+        )
+        .collect::<Vec<_>>();
+    report(&sources, || components::weakly_connected(g), policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::apl::AplValue;
+    use crate::graphs::{
+        csr::CSR,
+        matrix::Adjacency,
+        test_graphs::{clustered, graph_one, GRAPH_ONE_APL},
+        transform::Transform,
+        FromEdges, Graph,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn graph_one_lengths() {
+        // 0 and 1 reach 4 vertices at distance 1, 3 at 2 and 2 at 3, 2, 3 and 4 reach 5 vertices
+        // at distance 1 and 4 at 2 and 5..=9 only reach their clique
+        let expected = PathLengths {
+            total: 2 * 16 + 3 * 13 + 5 * 4,
+            pairs: 5 * 9 + 5 * 4,
+        };
+        let g = graph_one::<CSR>();
+        assert_eq!(path_lengths(&g), expected);
+        assert_eq!(par_path_lengths(&g), expected);
+        assert_eq!(bit_parallel_path_lengths(&g), expected);
+        assert_eq!(par_bit_parallel_path_lengths(&g), expected);
+        approx::assert_abs_diff_eq!(apl(&g), 91.0 / 65.0);
+        approx::assert_abs_diff_eq!(apl(&g.symmetrize(|a, _| a)), GRAPH_ONE_APL);
+        let report = apl_with(&g, Unreachable::Skip).unwrap();
+        assert_eq!(report.value, AplValue::Mean(91.0 / 65.0));
+        assert_eq!((report.connected_pairs, report.unreachable_pairs), (65, 25));
+        assert_eq!(apl_with(&g, Unreachable::Error), Err(UnreachablePairs(25)));
+        // the cut arcs make the whole graph a single weakly connected component
+        match apl_with(&g, Unreachable::PerComponent).unwrap().value {
+            AplValue::PerComponent(c) => assert_eq!(c.len(), 1),
+            v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn path() {
+        let g: Adjacency = crate::graph![Adjacency = (4) {
+            0 => 1; 1 => 0; 1 => 2; 2 => 1; 2 => 3; 3 => 2;
+        }];
+        assert_eq!(
+            path_lengths(&g),
+            PathLengths {
+                total: 2 * (1 + 2 + 3 + 1 + 2 + 1),
+                pairs: 12
+            }
+        );
+        assert_eq!(
+            path_lengths(&Adjacency::<()>::new()),
+            PathLengths::default()
+        );
+    }
+
+    #[test]
+    fn variants_agree() {
+        let adj = clustered::<Adjacency, _>(300, 3, 2, StdRng::seed_from_u64(0xBAD5EED));
+        let csr = CSR::<(), u32>::from_edges(
+            adj.vertices(),
+            adj.neighbourhoods()
+                .flat_map(|(f, tos)| tos.map(move |t| (f, t.to)))
+                .collect::<Vec<_>>(),
+        );
+        let expected = path_lengths(&adj);
+        assert_eq!(path_lengths(&csr), expected);
+        assert_eq!(par_path_lengths(&csr), expected);
+        assert_eq!(bit_parallel_path_lengths(&adj), expected);
+        assert_eq!(par_bit_parallel_path_lengths(&csr), expected);
+    }
+}
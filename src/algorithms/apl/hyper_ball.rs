@@ -1,7 +1,10 @@
+use super::{mean, report, AplReport, SourceDistances, Unreachable, UnreachablePairs};
 use crate::{
-    graphs::{csr::CSR, *},
+    algorithms::components,
+    graphs::*,
     util::hyper_counters::{CounterArray, HyperLogLogCounter},
 };
+use rayon::prelude::*;
 
 #[derive(Clone)]
 struct HyperBall<H: CounterArray<usize>> {
@@ -14,35 +17,223 @@ impl<H: CounterArray<usize> + Clone> HyperBall<H> {
     }
 }
 
-pub fn hyper_ball<H, E>(g: &CSR<E>, counters: H) -> f64
+/// Everything HyperBall estimates about a graph.
+///
+/// Distances are measured along the arcs of the graph, so `B(v, t)` is the set of vertices
+/// reachable from `v` in at most `t` steps. To get the centralities of the HyperBall paper, which
+/// use the distances *to* `v`, run it over the transposed graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperBallResult {
+    /// `N(t) = Σ |B(v, t)|` for each `t` until the balls stop growing.
+    pub neighbourhood_function: Vec<f64>,
+    /// `Σ d(v, w)` over the vertices `w` reachable from `v`.
+    pub sum_of_distances: Box<[f64]>,
+    /// `Σ 1 / d(v, w)` over the vertices `w` reachable from `v`.
+    pub harmonic_centrality: Box<[f64]>,
+    /// `|B(v, ∞)|`, which counts `v` itself.
+    pub reachable: Box<[f64]>,
+    /// How many times every counter was updated.
+    pub iterations: usize,
+}
+
+impl HyperBallResult {
+    /// The estimated distances from each vertex, not counting the vertex itself as reached.
+    pub fn distances(&self) -> Vec<SourceDistances> {
+        self.sum_of_distances
+            .iter()
+            .zip(self.reachable.iter())
+            .zip(self.harmonic_centrality.iter())
+            .map(|((&total, &reachable), &harmonic)| SourceDistances {
+                total,
+                reached: f64::max(reachable - 1.0, 0.0),
+                harmonic,
+            })
+            .collect()
+    }
+
+    /// The average length of the shortest paths between the pairs of distinct vertices
+    /// connected by a path.
+    pub fn apl(&self) -> f64 {
+        mean(&self.distances())
+    }
+
+    /// The average path length, handling unreachable pairs according to `policy`. `g` must be
+    /// the graph this was computed on and is only used to find its weakly connected components.
+    pub fn apl_with<G: Successors>(
+        &self,
+        g: &G,
+        policy: Unreachable,
+    ) -> Result<AplReport, UnreachablePairs> {
+        report(
+            &self.distances(),
+            || components::weakly_connected(g),
+            policy,
+        )
+    }
+
+    /// `1 / Σ d(v, w)`, or zero for vertices that can't reach any other.
+    pub fn closeness(&self) -> Vec<f64> {
+        self.sum_of_distances
+            .iter()
+            .map(|&s| if s > 0.0 { 1.0 / s } else { 0.0 })
+            .collect()
+    }
+
+    /// The 90th percentile effective diameter.
+    pub fn effective_diameter(&self) -> f64 {
+        self.effective_diameter_at(0.9)
+    }
+
+    /// The smallest `t` for which `N(t)` reaches `alpha` of its final value, linearly
+    /// interpolated between consecutive values of `t`.
+    pub fn effective_diameter_at(&self, alpha: f64) -> f64 {
+        let nf = &self.neighbourhood_function;
+        let target = match nf.last() {
+            Some(last) => alpha * last,
+            None => return 0.0,
+        };
+        match nf.iter().position(|&n| n >= target) {
+            None | Some(0) => 0.0,
+            Some(d) => (d - 1) as f64 + (target - nf[d - 1]) / (nf[d] - nf[d - 1]),
+        }
+    }
+}
+
+/// The per vertex estimates, updated every round.
+struct Balls {
+    sizes: Box<[f64]>,
+    sum_of_distances: Box<[f64]>,
+    harmonic_centrality: Box<[f64]>,
+}
+
+/// Sets `next` to the union of the ball of `v` and the balls of its successors and accounts for
+/// the vertices that were added at `distance`. Returns whether the ball grew.
+#[inline]
+#[allow(clippy::too_many_arguments)]
+fn grow<C>(
+    current: &[C],
+    v: usize,
+    successors: impl Iterator<Item = usize>,
+    next: &mut C,
+    size: &mut f64,
+    sum_of_distances: &mut f64,
+    harmonic_centrality: &mut f64,
+    distance: f64,
+) -> bool
+where
+    C: HyperLogLogCounter<usize> + Clone,
+{
+    next.clone_from(&current[v]);
+    let mut modified = false;
+    for w in successors {
+        modified = bool::max(current[w].union_onto(next), modified);
+    }
+    let new_size = next.estimate();
+    let delta = new_size - *size;
+    *sum_of_distances += distance * delta;
+    *harmonic_centrality += delta / distance;
+    *size = new_size;
+    modified
+}
+
+/// Runs rounds until no ball grows. `round` receives the current and next counters and the
+/// distance of the vertices added in that round and returns whether any ball grew.
+fn run<G, H, F>(g: &G, counters: H, mut round: F) -> HyperBallResult
 where
+    G: Graph,
     H: CounterArray<usize> + Clone,
+    F: FnMut(&H, &mut H, &mut Balls, f64) -> bool,
 {
     let mut ball = HyperBall::new(counters);
-    for v in g.nodes() {
+    for v in 0..g.vertices() {
         ball.counters[v].register(v);
     }
-    let mut apls = vec![0.0; g.vertices()].into_boxed_slice();
+    let n = g.vertices();
+    let mut balls = Balls {
+        sizes: ball.counters.iter().map(|c| c.estimate()).collect(),
+        sum_of_distances: vec![0.0; n].into_boxed_slice(),
+        harmonic_centrality: vec![0.0; n].into_boxed_slice(),
+    };
+    let mut neighbourhood_function = vec![balls.sizes.iter().sum::<f64>()];
     let mut modified = true;
     let mut t = 0;
     let mut new_counters = ball.counters.clone();
     while modified {
-        modified = false;
-        for (v, successors) in g.neighbourhoods().enumerate() {
-            new_counters[v].clone_from(&ball.counters[v]);
-            let a = &mut new_counters[v];
-            for w in successors {
-                modified = bool::max(ball.counters[w.to].union_onto(a), modified);
-            }
-            apls[v] += t as f64 * (a.estimate() - ball.counters[v].estimate());
+        // After this round the counters approximate the balls of radius t + 1
+        modified = round(
+            &ball.counters,
+            &mut new_counters,
+            &mut balls,
+            (t + 1) as f64,
+        );
+        std::mem::swap(&mut ball.counters, &mut new_counters);
+        if modified {
+            neighbourhood_function.push(balls.sizes.iter().sum());
         }
-        new_counters
-            .iter_mut()
-            .zip(ball.counters.iter_mut())
-            .for_each(|(new, old)| std::mem::swap(new, old));
         t += 1;
     }
-    apls.iter().sum::<f64>() / apls.len() as f64
+    HyperBallResult {
+        neighbourhood_function,
+        sum_of_distances: balls.sum_of_distances,
+        harmonic_centrality: balls.harmonic_centrality,
+        reachable: balls.sizes,
+        iterations: t,
+    }
+}
+
+pub fn hyper_ball<H, G>(g: &G, counters: H) -> HyperBallResult
+where
+    H: CounterArray<usize> + Clone,
+    G: Successors,
+{
+    run(g, counters, |current, next, balls, distance| {
+        let mut modified = false;
+        for v in 0..g.vertices() {
+            modified |= grow(
+                current,
+                v,
+                g.successors(v),
+                &mut next[v],
+                &mut balls.sizes[v],
+                &mut balls.sum_of_distances[v],
+                &mut balls.harmonic_centrality[v],
+                distance,
+            );
+        }
+        modified
+    })
+}
+
+/// Same as [`hyper_ball`] but each round is split among the threads of rayon's thread pool.
+///
+/// Every vertex is updated exactly as in the sequential version, so, given counters with the same
+/// seed, both return the same result.
+pub fn par_hyper_ball<H, G>(g: &G, counters: H) -> HyperBallResult
+where
+    H: CounterArray<usize> + Clone + Sync,
+    H::Counter: Send + Sync,
+    G: Successors + Sync,
+{
+    run(g, counters, |current, next, balls, distance| {
+        next.par_iter_mut()
+            .zip(balls.sizes.par_iter_mut())
+            .zip(balls.sum_of_distances.par_iter_mut())
+            .zip(balls.harmonic_centrality.par_iter_mut())
+            .enumerate()
+            .map(|(v, (((next, size), sum), harmonic))| {
+                grow(
+                    current,
+                    v,
+                    g.successors(v),
+                    next,
+                    size,
+                    sum,
+                    harmonic,
+                    distance,
+                )
+            })
+            .reduce(|| false, |a, b| a || b)
+    })
 }
 
 #[cfg(test)]
@@ -51,6 +242,7 @@ mod tests {
     use crate::{
         graphs::{
             csr::CSR,
+            matrix::Adjacency,
             test_graphs::{graph_one, GRAPH_ONE_APL},
         },
         util::hyper_counters::*,
@@ -63,7 +255,8 @@ mod tests {
         let apl = hyper_ball(
             &g,
             vec![HyperLogLog::new_with_seed(B::B4, SEED); g.vertices()].into_boxed_slice(),
-        );
+        )
+        .apl();
         eprintln!("APL: {}", apl);
         approx::assert_relative_eq!(apl, GRAPH_ONE_APL, max_relative = 1.0);
     }
@@ -74,7 +267,8 @@ mod tests {
         let apl = hyper_ball(
             &g,
             CompactHyperLogLogArray::new_with_seed(B::B4, g.vertices(), SEED),
-        );
+        )
+        .apl();
         eprintln!("APL: {}", apl);
         approx::assert_relative_eq!(apl, GRAPH_ONE_APL, max_relative = 1.0);
     }
@@ -93,4 +287,62 @@ mod tests {
             )
         )
     }
+
+    #[test]
+    fn parallel_equivalence() {
+        let g = crate::graphs::test_graphs::clustered::<Adjacency, _>(
+            500,
+            4,
+            2,
+            <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(SEED),
+        );
+        let g = CSR::from(g);
+        assert_eq!(
+            hyper_ball(
+                &g,
+                vec![HyperLogLog::new_with_seed(B::B6, SEED); g.vertices()].into_boxed_slice(),
+            ),
+            par_hyper_ball(
+                &g,
+                vec![HyperLogLog::new_with_seed(B::B6, SEED); g.vertices()].into_boxed_slice(),
+            )
+        );
+        assert_eq!(
+            hyper_ball(
+                &g,
+                CompactHyperLogLogArray::new_with_seed(B::B6, g.vertices(), SEED)
+            ),
+            par_hyper_ball(
+                &g,
+                CompactHyperLogLogArray::new_with_seed(B::B6, g.vertices(), SEED)
+            )
+        );
+    }
+
+    #[test]
+    fn per_vertex() {
+        let g = graph_one::<CSR<()>>();
+        let r = hyper_ball(
+            &g,
+            vec![HyperLogLog::new_with_seed(B::B12, SEED); g.vertices()].into_boxed_slice(),
+        );
+        // 0 reaches 1..=4 in one step, 5..=7 in two and 8, 9 in three
+        approx::assert_relative_eq!(r.reachable[0], 10.0, max_relative = 0.1);
+        approx::assert_relative_eq!(r.sum_of_distances[0], 16.0, max_relative = 0.1);
+        approx::assert_relative_eq!(
+            r.harmonic_centrality[0],
+            4.0 + 1.5 + 2.0 / 3.0,
+            max_relative = 0.1
+        );
+        // 5 only reaches its own clique
+        approx::assert_relative_eq!(r.reachable[5], 5.0, max_relative = 0.1);
+        approx::assert_relative_eq!(r.sum_of_distances[5], 4.0, max_relative = 0.1);
+        approx::assert_relative_eq!(r.closeness()[5], 0.25, max_relative = 0.1);
+        // the balls stop growing after the third round
+        assert_eq!(r.neighbourhood_function.len(), 4);
+        assert_eq!(r.iterations, 4);
+        approx::assert_relative_eq!(r.neighbourhood_function[0], 10.0, max_relative = 0.1);
+        let d = r.effective_diameter();
+        assert!(d > 1.0 && d <= 3.0, "effective diameter: {}", d);
+    }
 }
@@ -0,0 +1,256 @@
+//! Average path length estimated from breadth first searches out of uniformly sampled sources.
+//!
+//! The estimate is the ratio between the sum of the distances found and the number of vertices
+//! reached, so, like [`bfs::apl`](super::bfs::apl), it skips unreachable pairs. Sources are drawn
+//! without replacement and the standard error is that of a ratio estimator, with the finite
+//! population correction, so sampling every vertex gives the exact value with no error.
+use super::bfs::Bfs;
+use crate::graphs::Successors;
+use rand::Rng;
+
+/// Sampled estimates need at least this many sources before their error is trusted.
+pub const MIN_SAMPLES: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub apl: f64,
+    pub standard_error: f64,
+    /// The two sided confidence interval at `confidence`.
+    pub confidence_interval: (f64, f64),
+    pub confidence: f64,
+    /// How many sources were searched from.
+    pub samples: usize,
+}
+
+impl Estimate {
+    /// Half the width of the confidence interval over the estimate.
+    pub fn relative_error(&self) -> f64 {
+        let (low, high) = self.confidence_interval;
+        if self.apl > 0.0 {
+            (high - low) / (2.0 * self.apl)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Running sums of the distances (`t`) and reached vertices (`r`) of each source.
+#[derive(Default)]
+struct Sums {
+    k: usize,
+    t: f64,
+    r: f64,
+    tt: f64,
+    rr: f64,
+    tr: f64,
+}
+
+impl Sums {
+    fn add(&mut self, t: f64, r: f64) {
+        self.k += 1;
+        self.t += t;
+        self.r += r;
+        self.tt += t * t;
+        self.rr += r * r;
+        self.tr += t * r;
+    }
+
+    fn estimate(&self, n: usize, confidence: f64) -> Estimate {
+        let k = self.k as f64;
+        let apl = if self.r > 0.0 { self.t / self.r } else { 0.0 };
+        let standard_error = if self.k == n || self.r == 0.0 {
+            0.0
+        } else if self.k < 2 {
+            f64::INFINITY
+        } else {
+            let residuals = self.tt - 2.0 * apl * self.tr + apl * apl * self.rr;
+            let variance = f64::max(residuals, 0.0) / (k - 1.0);
+            let correction = (n - self.k) as f64 / (n - 1) as f64;
+            (variance / k * correction).sqrt() / (self.r / k)
+        };
+        let margin = normal_quantile(0.5 + confidence / 2.0) * standard_error;
+        Estimate {
+            apl,
+            standard_error,
+            confidence_interval: (apl - margin, apl + margin),
+            confidence,
+            samples: self.k,
+        }
+    }
+}
+
+/// Draws distinct vertices in a uniformly random order, one partial Fisher–Yates step at a time.
+struct Sources {
+    order: Vec<usize>,
+    next: usize,
+}
+
+impl Sources {
+    fn new(n: usize) -> Self {
+        Self {
+            order: (0..n).collect(),
+            next: 0,
+        }
+    }
+
+    fn draw<R: Rng>(&mut self, rng: &mut R) -> Option<usize> {
+        if self.next == self.order.len() {
+            return None;
+        }
+        let i = rng.gen_range(self.next, self.order.len());
+        self.order.swap(self.next, i);
+        self.next += 1;
+        Some(self.order[self.next - 1])
+    }
+}
+
+/// Estimates the average path length from `k` sources, or from every vertex if the graph has
+/// fewer than `k`.
+pub fn apl<G, R>(g: &G, k: usize, confidence: f64, mut rng: R) -> Estimate
+where
+    G: Successors,
+    R: Rng,
+{
+    let n = g.vertices();
+    let mut bfs = Bfs::new(n);
+    let mut sources = Sources::new(n);
+    let mut sums = Sums::default();
+    for _ in 0..k {
+        match sources.draw(&mut rng) {
+            Some(s) => {
+                let (lengths, _) = bfs.run(g, s);
+                sums.add(lengths.total as f64, lengths.pairs as f64);
+            }
+            None => break,
+        }
+    }
+    sums.estimate(n, confidence)
+}
+
+/// Keeps sampling sources until the confidence interval is within `relative_error` of the
+/// estimate, after at least [`MIN_SAMPLES`] sources, or until every vertex was searched from.
+pub fn apl_until<G, R>(g: &G, relative_error: f64, confidence: f64, mut rng: R) -> Estimate
+where
+    G: Successors,
+    R: Rng,
+{
+    let n = g.vertices();
+    let mut bfs = Bfs::new(n);
+    let mut sources = Sources::new(n);
+    let mut sums = Sums::default();
+    while let Some(s) = sources.draw(&mut rng) {
+        let (lengths, _) = bfs.run(g, s);
+        sums.add(lengths.total as f64, lengths.pairs as f64);
+        if sums.k >= MIN_SAMPLES {
+            let estimate = sums.estimate(n, confidence);
+            if estimate.relative_error() <= relative_error {
+                return estimate;
+            }
+        }
+    }
+    sums.estimate(n, confidence)
+}
+
+/// The inverse of the standard normal CDF, using Acklam's rational approximation, which has a
+/// relative error below `1.15e-9`.
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p <= 0.0 {
+        f64::NEG_INFINITY
+    } else if p >= 1.0 {
+        f64::INFINITY
+    } else if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{csr::CSR, matrix::Adjacency, test_graphs, transform::Transform, Graph};
+    use rand::{rngs::StdRng, SeedableRng};
+    const SEED: u64 = 0xBAD5EED;
+
+    fn clustered(n: usize) -> CSR {
+        let adj = test_graphs::clustered::<Adjacency, _>(n, 4, 2, StdRng::seed_from_u64(SEED));
+        CSR::from(adj)
+    }
+
+    #[test]
+    fn quantile() {
+        approx::assert_abs_diff_eq!(normal_quantile(0.5), 0.0);
+        approx::assert_abs_diff_eq!(normal_quantile(0.975), 1.959_964, epsilon = 1e-6);
+        approx::assert_abs_diff_eq!(normal_quantile(0.01), -2.326_348, epsilon = 1e-6);
+        approx::assert_abs_diff_eq!(normal_quantile(0.995), 2.575_829, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn every_source_is_exact() {
+        let g = test_graphs::graph_one::<CSR>().symmetrize(|a, _| a);
+        let e = apl(&g, 100, 0.95, StdRng::seed_from_u64(SEED));
+        assert_eq!(e.samples, g.vertices());
+        approx::assert_abs_diff_eq!(e.apl, test_graphs::GRAPH_ONE_APL);
+        assert_eq!(e.standard_error, 0.0);
+        assert_eq!(e.confidence_interval, (e.apl, e.apl));
+    }
+
+    #[test]
+    fn estimate_covers_exact() {
+        let g = clustered(1000);
+        let exact = super::super::bfs::apl(&g);
+        let e = apl(&g, 200, 0.99, StdRng::seed_from_u64(SEED));
+        assert_eq!(e.samples, 200);
+        let (low, high) = e.confidence_interval;
+        assert!(low <= exact && exact <= high, "{} not in {:?}", exact, e);
+    }
+
+    #[test]
+    fn adaptive() {
+        let g = clustered(1000);
+        let e = apl_until(&g, 0.01, 0.95, StdRng::seed_from_u64(SEED));
+        assert!(e.samples >= MIN_SAMPLES && e.samples < g.vertices());
+        assert!(e.relative_error() <= 0.01, "{:?}", e);
+        let exact = super::super::bfs::apl(&g);
+        approx::assert_relative_eq!(e.apl, exact, max_relative = 0.03);
+    }
+}
@@ -0,0 +1,230 @@
+//! Exact average path length of weighted graphs, running Dijkstra's algorithm from every vertex.
+use super::{report, AplReport, SourceDistances, Unreachable, UnreachablePairs};
+use crate::{
+    algorithms::{components, shortest_paths::dijkstra},
+    graphs::WSuccessors,
+};
+use rayon::prelude::*;
+use std::ops::Add;
+
+/// Edge weights whose path lengths can be summed as floats, as the sum over all pairs would
+/// overflow the weight type itself long before any single path does.
+pub trait AsF64: Copy {
+    fn as_f64(self) -> f64;
+}
+
+macro_rules! impl_as_f64 {
+    ($($t:ty),*) => {
+        $(
+        impl AsF64 for $t {
+            fn as_f64(self) -> f64 {
+                self as f64
+            }
+        }
+        )*
+    };
+}
+impl_as_f64!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The weighted lengths of the shortest paths between all pairs of distinct vertices connected
+/// by a path.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WeightedLengths {
+    pub total: f64,
+    /// The number of ordered pairs `(u, v)`, `u != v`, such that `v` is reachable from `u`.
+    pub pairs: usize,
+}
+
+impl Add for WeightedLengths {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            total: self.total + other.total,
+            pairs: self.pairs + other.pairs,
+        }
+    }
+}
+
+impl WeightedLengths {
+    pub fn apl(&self) -> f64 {
+        if self.pairs == 0 {
+            0.0
+        } else {
+            self.total / self.pairs as f64
+        }
+    }
+}
+
+fn from_source<G, W>(g: &G, source: usize) -> SourceDistances
+where
+    G: WSuccessors<EdgeWeight = W>,
+    W: Ord + Add<Output = W> + Default + AsF64,
+{
+    dijkstra(g, source)
+        .distances
+        .into_iter()
+        .enumerate()
+        .filter(|&(v, _)| v != source)
+        .filter_map(|(_, d)| d)
+        .map(AsF64::as_f64)
+        .fold(SourceDistances::default(), |sd, d| SourceDistances {
+            total: sd.total + d,
+            reached: sd.reached + 1.0,
+            harmonic: sd.harmonic + 1.0 / d,
+        })
+}
+
+impl From<SourceDistances> for WeightedLengths {
+    fn from(sd: SourceDistances) -> Self {
+        Self {
+            total: sd.total,
+            pairs: sd.reached as usize,
+        }
+    }
+}
+
+/// Runs Dijkstra's algorithm from every vertex, one at a time.
+pub fn path_lengths<G, W>(g: &G) -> WeightedLengths
+where
+    G: WSuccessors<EdgeWeight = W>,
+    W: Ord + Add<Output = W> + Default + AsF64,
+{
+    (0..g.vertices())
+        .map(|s| from_source(g, s).into())
+        .fold(WeightedLengths::default(), Add::add)
+}
+
+/// Runs Dijkstra's algorithm from every vertex, splitting the sources among the threads of
+/// rayon's thread pool.
+pub fn par_path_lengths<G, W>(g: &G) -> WeightedLengths
+where
+    G: WSuccessors<EdgeWeight = W> + Sync,
+    W: Ord + Add<Output = W> + Default + AsF64 + Send,
+{
+    (0..g.vertices())
+        .into_par_iter()
+        .map(|s| from_source(g, s).into())
+        .reduce(WeightedLengths::default, Add::add)
+}
+
+/// The exact weighted average path length between connected pairs of vertices.
+pub fn apl<G, W>(g: &G) -> f64
+where
+    G: WSuccessors<EdgeWeight = W> + Sync,
+    W: Ord + Add<Output = W> + Default + AsF64 + Send,
+{
+    par_path_lengths(g).apl()
+}
+
+/// The exact weighted average path length, handling unreachable pairs according to `policy`.
+///
+/// Zero weight arcs are allowed, but two distinct vertices at distance zero have an infinite
+/// inverse distance, so any such pair makes the [`Unreachable::Harmonic`] mean zero.
+pub fn apl_with<G, W>(g: &G, policy: Unreachable) -> Result<AplReport, UnreachablePairs>
+where
+    G: WSuccessors<EdgeWeight = W> + Sync,
+    W: Ord + Add<Output = W> + Default + AsF64 + Send,
+{
+    let sources = (0..g.vertices())
+        .into_par_iter()
+        .map(|s| from_source(g, s))
+        .collect::<Vec<_>>();
+    report(&sources, || components::weakly_connected(g), policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::apl::{bfs, AplValue},
+        graphs::{
+            csr::CSR, matrix::Adjacency, test_graphs, transform::Transform, Graph, WFromEdges,
+        },
+    };
+
+    #[test]
+    fn unit_weights_match_bfs() {
+        let g = test_graphs::graph_one::<CSR>().symmetrize(|a, _| a);
+        let edges = g
+            .nodes()
+            .flat_map(|v| g.neighbours(v).map(move |w| (v, w.to, 1u32)))
+            .collect::<Vec<_>>();
+        let weighted: CSR<u32> = WFromEdges::from_edges(g.vertices(), edges);
+        let expected = bfs::path_lengths(&g);
+        assert_eq!(
+            path_lengths(&weighted),
+            WeightedLengths {
+                total: expected.total as f64,
+                pairs: expected.pairs
+            }
+        );
+        assert_eq!(par_path_lengths(&weighted), path_lengths(&weighted));
+        approx::assert_abs_diff_eq!(apl(&weighted), test_graphs::GRAPH_ONE_APL);
+    }
+
+    #[test]
+    fn triangle() {
+        // the long side is never used
+        let g: Adjacency<u32> = crate::graph![Adjacency<u32> = (3) {
+            0 => 1, 1; 1 => 0, 1; 1 => 2, 2; 2 => 1, 2; 0 => 2, 10; 2 => 0, 10;
+        }];
+        assert_eq!(
+            path_lengths(&g),
+            WeightedLengths {
+                total: 2.0 * (1.0 + 2.0 + 3.0),
+                pairs: 6
+            }
+        );
+        let report = apl_with(&g, Unreachable::Harmonic).unwrap();
+        match report.value {
+            AplValue::Harmonic(h) => {
+                approx::assert_abs_diff_eq!(h, 6.0 / (2.0 * (1.0 + 0.5 + 1.0 / 3.0)))
+            }
+            v => panic!("{:?}", v),
+        }
+        assert_eq!(report.unreachable_pairs, 0);
+    }
+
+    #[test]
+    fn sums_past_the_weight_type() {
+        // every path fits in a u32 but their sum doesn't
+        let star: CSR<u32> = WFromEdges::from_edges(
+            4,
+            vec![
+                (0, 1, 2_000_000_000),
+                (0, 2, 2_000_000_000),
+                (0, 3, 2_000_000_000),
+            ],
+        );
+        assert_eq!(
+            path_lengths(&star),
+            WeightedLengths {
+                total: 6e9,
+                pairs: 3
+            }
+        );
+        approx::assert_abs_diff_eq!(apl(&star), 2e9);
+        let wide: Adjacency<i64> =
+            crate::graph![Adjacency<i64> = (3) { 0 => 1, 1 << 40; 1 => 2, 1 }];
+        assert_eq!(
+            par_path_lengths(&wide).total,
+            2.0 * (1u64 << 40) as f64 + 2.0
+        );
+        let wide: Adjacency<usize> = crate::graph![Adjacency<usize> = (2) { 0 => 1, 7 }];
+        approx::assert_abs_diff_eq!(apl(&wide), 7.0);
+    }
+
+    #[test]
+    fn zero_weights() {
+        let g: Adjacency<u8> = crate::graph![Adjacency<u8> = (3) { 0 => 1, 0; 1 => 2, 2 }];
+        assert_eq!(
+            path_lengths(&g),
+            WeightedLengths {
+                total: 4.0,
+                pairs: 3
+            }
+        );
+        let report = apl_with(&g, Unreachable::Harmonic).unwrap();
+        assert_eq!(report.value, AplValue::Harmonic(0.0));
+    }
+}
@@ -1,36 +1,334 @@
-use crate::graphs::{RandomAccess, To};
+pub mod directed;
+pub mod estimator;
+pub mod exact;
+
+use crate::graphs::{
+    compressed::CompressedCSR, mapped::MappedCSR, subgraph::Subgraph, HasLink, RandomAccess,
+    Successors, VertexIndex,
+};
+use bytemuck::Pod;
 use rand::{seq::SliceRandom, Rng};
+use rustc_hash::FxHashMap;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// No vertex has two distinct neighbours other than itself.
+    NoEligibleVertices { excluded: usize },
+    /// Zero wedges were asked for.
+    NoSamples,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoEligibleVertices { excluded } => write!(
+                f,
+                "none of the {} vertices has two distinct neighbours",
+                excluded
+            ),
+            Error::NoSamples => f.write_str("the number of samples must be positive"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The wedges of a graph, pairs of distinct neighbours of the same vertex, ready to be sampled.
+///
+/// Self loops and repeated arcs don't form wedges, so vertices with less than two distinct
+/// neighbours other than themselves are excluded. The neighbourhoods with loops or repeats are
+/// cleaned up once, when this is built, so sampling never has to retry.
+pub struct Wedges<'g, G> {
+    g: &'g G,
+    lookup: Lookup<G>,
+    eligible: Vec<usize>,
+    /// For the eligible vertices whose neighbourhood had loops or repeats, the position in
+    /// `g.successors(v)` of the first arc to each distinct neighbour.
+    cleaned: FxHashMap<usize, Vec<usize>>,
+    excluded: usize,
+}
+
+/// How [`Wedges`] finds a successor of a vertex by its position, in constant time.
+enum Lookup<G> {
+    /// The successor at a position and the number of successors, indexing the neighbours of a
+    /// [`RandomAccess`] graph.
+    Indexed {
+        successor: fn(&G, usize, usize) -> usize,
+        degree: fn(&G, usize) -> usize,
+    },
+    /// A copy of the successors of the eligible vertices laid out like a CSR, for the graphs
+    /// that can only iterate over them.
+    Table {
+        row_indexes: Box<[usize]>,
+        successors: Vec<usize>,
+    },
+}
+
+impl<'g, G: RandomAccess> Wedges<'g, G> {
+    pub fn new(g: &'g G) -> Self {
+        Self::with_lookup(
+            g,
+            Lookup::Indexed {
+                successor: |g, v, i| g.neighbours(v)[i].to.index(),
+                degree: |g, v| g.neighbours(v).len(),
+            },
+        )
+    }
+}
+
+impl<'g, G: Successors + HasLink> Wedges<'g, G> {
+    /// Copies the successors of the vertices with wedges, for graphs that aren't
+    /// [`RandomAccess`].
+    pub fn tabulated(g: &'g G) -> Self {
+        let mut row_indexes = Vec::with_capacity(g.vertices() + 1);
+        let mut successors = Vec::new();
+        row_indexes.push(0);
+        for v in 0..g.vertices() {
+            let start = successors.len();
+            successors.extend(g.successors(v));
+            if successors.len() - start < 2 {
+                successors.truncate(start);
+            }
+            row_indexes.push(successors.len());
+        }
+        Self::with_lookup(
+            g,
+            Lookup::Table {
+                row_indexes: row_indexes.into_boxed_slice(),
+                successors,
+            },
+        )
+    }
+
+    fn with_lookup(g: &'g G, lookup: Lookup<G>) -> Self {
+        let mut eligible = Vec::new();
+        let mut cleaned = FxHashMap::default();
+        let (mut neighbours, mut seen) = (Vec::new(), Vec::new());
+        for v in 0..g.vertices() {
+            neighbours.clear();
+            neighbours.extend(g.successors(v));
+            if neighbours.len() < 2 {
+                continue;
+            }
+            seen.clear();
+            seen.extend_from_slice(&neighbours);
+            seen.sort_unstable();
+            let clean = seen.windows(2).all(|w| w[0] != w[1]) && seen.binary_search(&v).is_err();
+            if clean {
+                eligible.push(v);
+                continue;
+            }
+            let mut positions = (0..neighbours.len())
+                .filter(|&i| neighbours[i] != v)
+                .collect::<Vec<_>>();
+            positions.sort_unstable_by_key(|&i| (neighbours[i], i));
+            positions.dedup_by_key(|i| neighbours[*i]);
+            if positions.len() >= 2 {
+                cleaned.insert(v, positions);
+                eligible.push(v);
+            }
+        }
+        Self {
+            g,
+            lookup,
+            excluded: g.vertices() - eligible.len(),
+            eligible,
+            cleaned,
+        }
+    }
+
+    /// The vertices with at least two distinct neighbours other than themselves.
+    pub fn eligible(&self) -> &[usize] {
+        &self.eligible
+    }
+
+    /// How many vertices have no wedges.
+    pub fn excluded(&self) -> usize {
+        self.excluded
+    }
+
+    /// The successor of the eligible vertex `v` at position `i` in `g.successors(v)`.
+    fn successor(&self, v: usize, i: usize) -> usize {
+        match &self.lookup {
+            Lookup::Indexed { successor, .. } => successor(self.g, v, i),
+            Lookup::Table {
+                row_indexes,
+                successors,
+            } => successors[row_indexes[v] + i],
+        }
+    }
+
+    /// The number of distinct neighbours of an eligible vertex, itself excluded.
+    pub fn degree(&self, v: usize) -> usize {
+        match (self.cleaned.get(&v), &self.lookup) {
+            (Some(positions), _) => positions.len(),
+            (None, Lookup::Indexed { degree, .. }) => degree(self.g, v),
+            (None, Lookup::Table { row_indexes, .. }) => row_indexes[v + 1] - row_indexes[v],
+        }
+    }
+
+    /// The position in `g.successors(v)` of the `i`th distinct neighbour of `v`.
+    pub fn position(&self, v: usize, i: usize) -> usize {
+        self.cleaned.get(&v).map_or(i, |p| p[i])
+    }
 
-pub fn c_coef<G, R>(k: i32, node_indexes: &[usize], g: &G, mut rng: R) -> f64
+    /// The positions in `g.successors(v)` of two distinct neighbours of the eligible vertex
+    /// `v`, uniformly at random.
+    pub fn sample<R: Rng>(&self, v: usize, rng: &mut R) -> (usize, usize) {
+        let d = self.degree(v);
+        let i = rng.gen_range(0, d);
+        let j = rng.gen_range(0, d - 1);
+        let j = if j >= i { j + 1 } else { j };
+        (self.position(v, i), self.position(v, j))
+    }
+
+    /// Samples a wedge of `v` and checks whether it is closed.
+    pub fn closed<R: Rng>(&self, v: usize, rng: &mut R) -> bool {
+        let (i, j) = self.sample(v, rng);
+        self.g.has_link(self.successor(v, i), self.successor(v, j))
+    }
+}
+
+/// Graphs whose wedges can be sampled in constant time: the [`RandomAccess`] ones index their
+/// neighbours, the others have the successors of their vertices with wedges copied.
+pub trait SampleWedges: Successors + HasLink + Sized {
+    fn wedges(&self) -> Wedges<'_, Self>;
+}
+
+impl<G: RandomAccess> SampleWedges for G {
+    fn wedges(&self) -> Wedges<'_, Self> {
+        Wedges::new(self)
+    }
+}
+
+impl SampleWedges for CompressedCSR {
+    fn wedges(&self) -> Wedges<'_, Self> {
+        Wedges::tabulated(self)
+    }
+}
+
+impl<E: Pod> SampleWedges for MappedCSR<E> {
+    fn wedges(&self) -> Wedges<'_, Self> {
+        Wedges::tabulated(self)
+    }
+}
+
+impl<G, F> SampleWedges for Subgraph<'_, G, F>
+where
+    G: Successors + HasLink,
+    F: Fn(usize, usize) -> bool,
+{
+    fn wedges(&self) -> Wedges<'_, Self> {
+        Wedges::tabulated(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coefficient {
+    pub value: f64,
+    /// How many vertices weren't sampled because they have no wedges.
+    pub excluded: usize,
+}
+
+/// Estimates the average local clustering coefficient of the vertices with wedges from `k`
+/// sampled wedges.
+///
+/// Wedges are made of successors and closed by an arc, so this is the coefficient of
+/// [`exact::Triangles::average_local`] only if the graph stores both directions of its edges.
+pub fn c_coef<G, R>(k: usize, g: &G, mut rng: R) -> Result<Coefficient, Error>
 where
     R: Rng,
-    G: RandomAccess,
+    G: SampleWedges,
 {
-    if node_indexes.is_empty() {
-        return 0.0;
+    let wedges = g.wedges();
+    if wedges.eligible.is_empty() {
+        return Err(Error::NoEligibleVertices {
+            excluded: wedges.excluded,
+        });
     }
-    let mut l = 0i32;
+    if k == 0 {
+        return Err(Error::NoSamples);
+    }
+    let mut l = 0;
     for _ in 0..k {
-        let j = *node_indexes.choose(&mut rng).unwrap();
-        let (u, w) = g
-            .neighbours(j)
-            .choose(&mut rng)
-            .and_then(|To { to: u, .. }| match &g.neighbours(j) {
-                [w, _] if w != u => Some((*u, w.to)),
-                [_, w] if w != u => Some((*u, w.to)),
-                a => loop {
-                    let w = &a.choose(&mut rng)?.to;
-                    if w != u {
-                        return Some((*u, *w));
-                    }
-                },
-            })
-            .unwrap_or_else(|| {
-                panic!("Node '{}' with outdgree = {} < 2", j, g.neighbours(j).len())
-            });
-        if g.has_link(u, w) {
+        let j = *wedges.eligible.choose(&mut rng).unwrap();
+        if wedges.closed(j, &mut rng) {
             l += 1
         }
     }
-    l as f64 / k as f64
+    Ok(Coefficient {
+        value: l as f64 / k as f64,
+        excluded: wedges.excluded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::csr::CSR;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn degenerate_vertices() {
+        // 0 only has a loop and a repeated arc to 1, 1 and 2 form a triangle with 3, 4 is alone
+        let g: CSR = crate::graph![CSR = (5) {
+            0 => 0; 0 => 1; 0 => 1;
+            1 => 1; 1 => 2; 1 => 3; 1 => 2;
+            2 => 1; 2 => 3;
+            3 => 1; 3 => 2;
+        }];
+        let wedges = Wedges::new(&g);
+        assert_eq!(wedges.eligible(), &[1, 2, 3]);
+        assert_eq!(wedges.excluded(), 2);
+        assert_eq!(wedges.degree(1), 2);
+        let c = c_coef(1000, &g, StdRng::seed_from_u64(0xBAD5EED)).unwrap();
+        assert_eq!(
+            c,
+            Coefficient {
+                value: 1.0,
+                excluded: 2
+            }
+        );
+    }
+
+    #[test]
+    fn tabulated() {
+        let g: CSR = crate::graph![CSR = (5) {
+            0 => 0; 0 => 1; 0 => 1;
+            1 => 1; 1 => 2; 1 => 3; 1 => 2;
+            2 => 1; 2 => 3; 2 => 4;
+            3 => 1; 3 => 2;
+        }];
+        let (indexed, table) = (Wedges::new(&g), Wedges::tabulated(&g));
+        assert_eq!(table.eligible(), indexed.eligible());
+        assert_eq!(table.excluded(), indexed.excluded());
+        for &v in indexed.eligible() {
+            assert_eq!(table.degree(v), indexed.degree(v));
+            let (mut a, mut b) = (
+                StdRng::seed_from_u64(v as u64),
+                StdRng::seed_from_u64(v as u64),
+            );
+            for _ in 0..100 {
+                assert_eq!(table.closed(v, &mut a), indexed.closed(v, &mut b));
+            }
+        }
+        let compressed = CompressedCSR::from_csr(&g);
+        assert_eq!(
+            c_coef(1000, &compressed, StdRng::seed_from_u64(0xBAD5EED)),
+            c_coef(1000, &g, StdRng::seed_from_u64(0xBAD5EED))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let g: CSR = crate::graph![CSR = (3) { 0 => 0; 0 => 1; 0 => 1; 1 => 2 }];
+        let rng = StdRng::seed_from_u64(0xBAD5EED);
+        assert_eq!(
+            c_coef(10, &g, rng.clone()),
+            Err(Error::NoEligibleVertices { excluded: 3 })
+        );
+        let g: CSR = crate::graph![CSR = (3) { 0 => 1; 0 => 2 }];
+        assert_eq!(c_coef(0, &g, rng), Err(Error::NoSamples));
+    }
 }
@@ -0,0 +1,294 @@
+//! Fagiolo's clustering coefficients for directed graphs.
+//!
+//! Each coefficient counts a different kind of directed triangle around a vertex `i`, with `j`
+//! and `h` two of its neighbours:
+//!
+//! - cycle: `i -> j -> h -> i`
+//! - middleman: `h -> i -> j` and `h -> j`
+//! - in: `j -> i`, `h -> i` and `j -> h`
+//! - out: `i -> j`, `i -> h` and `j -> h`
+//!
+//! and divides it by the number of those triangles the arcs of `i` could form. Self loops and
+//! repeated arcs are ignored.
+use super::estimator::{hoeffding_error, Estimate};
+use crate::graphs::{
+    csr::{ReverseAdjacency, CSR},
+    sorted::Sorted,
+    Graph, HasLink, VertexIndex,
+};
+use itertools::{EitherOrBoth, Itertools};
+use rand::{seq::SliceRandom, Rng};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motif {
+    Cycle,
+    Middleman,
+    In,
+    Out,
+}
+
+impl Motif {
+    pub const ALL: [Motif; 4] = [Motif::Cycle, Motif::Middleman, Motif::In, Motif::Out];
+}
+
+/// The successors and predecessors of every vertex of a graph with sorted neighbours.
+///
+/// The graph is borrowed, so the only thing built is the index of the predecessors. Self loops
+/// and repeated arcs are skipped as the rows are read.
+pub struct Directed<'g, E, Ix = usize> {
+    graph: &'g Sorted<CSR<E, Ix>>,
+    reverse: ReverseAdjacency<'g, E, Ix>,
+}
+
+/// The distinct vertices of a sorted row other than `v`.
+fn distinct<I>(v: usize, row: I) -> impl Iterator<Item = usize> + Clone
+where
+    I: Iterator<Item = usize> + Clone,
+{
+    row.dedup().filter(move |&w| w != v)
+}
+
+/// The size of the intersection of two sorted sets.
+fn intersection<A, B>(a: A, b: B) -> usize
+where
+    A: Iterator<Item = usize>,
+    B: Iterator<Item = usize>,
+{
+    a.merge_join_by(b, usize::cmp)
+        .filter(|e| matches!(e, EitherOrBoth::Both(..)))
+        .count()
+}
+
+/// Two distinct elements of `set`, of size `len`, uniformly at random.
+fn pair<I, R>(set: I, len: usize, rng: &mut R) -> (usize, usize)
+where
+    I: Iterator<Item = usize> + Clone,
+    R: Rng,
+{
+    let i = rng.gen_range(0, len);
+    let j = rng.gen_range(0, len - 1);
+    let j = if j >= i { j + 1 } else { j };
+    (set.clone().nth(i).unwrap(), set.clone().nth(j).unwrap())
+}
+
+impl<'g, E, Ix: VertexIndex> Directed<'g, E, Ix> {
+    pub fn new(g: &'g Sorted<CSR<E, Ix>>) -> Self {
+        Self {
+            graph: g,
+            reverse: g.reverse_adjacency(),
+        }
+    }
+
+    /// The distinct successors of `v` other than itself, in increasing order.
+    fn successors(&self, v: usize) -> impl Iterator<Item = usize> + Clone + 'g {
+        distinct(v, self.reverse.successors(v).iter().map(|w| w.to.index()))
+    }
+
+    /// The distinct predecessors of `v` other than itself, in increasing order.
+    fn predecessors(&self, v: usize) -> impl Iterator<Item = usize> + Clone + '_ {
+        distinct(v, self.reverse.predecessors(v).iter().map(|u| u.index()))
+    }
+
+    /// The number of vertices linked to `v` in both directions.
+    pub fn reciprocated(&self, v: usize) -> usize {
+        intersection(self.successors(v), self.predecessors(v))
+    }
+
+    /// The number of `motif` triangles `v` could be part of.
+    pub fn possible(&self, v: usize, motif: Motif) -> usize {
+        let d_in = self.predecessors(v).count();
+        let d_out = self.successors(v).count();
+        match motif {
+            Motif::Cycle | Motif::Middleman => d_in * d_out - self.reciprocated(v),
+            Motif::In => d_in * d_in.saturating_sub(1),
+            Motif::Out => d_out * d_out.saturating_sub(1),
+        }
+    }
+
+    /// The number of `motif` triangles `v` is part of.
+    pub fn triangles(&self, v: usize, motif: Motif) -> usize {
+        let (ins, outs) = (self.predecessors(v), self.successors(v));
+        match motif {
+            Motif::Cycle => outs
+                .map(|j| intersection(self.successors(j), ins.clone()))
+                .sum(),
+            Motif::Middleman => outs
+                .map(|j| intersection(self.predecessors(j), ins.clone()))
+                .sum(),
+            Motif::In => ins
+                .clone()
+                .map(|j| intersection(self.successors(j), ins.clone()))
+                .sum(),
+            Motif::Out => outs
+                .clone()
+                .map(|j| intersection(self.successors(j), outs.clone()))
+                .sum(),
+        }
+    }
+
+    /// The local coefficient of each vertex, or `None` if it can't be part of any triangle.
+    pub fn local(&self, motif: Motif) -> Vec<Option<f64>> {
+        (0..self.graph.vertices())
+            .map(|v| match self.possible(v, motif) {
+                0 => None,
+                p => Some(self.triangles(v, motif) as f64 / p as f64),
+            })
+            .collect()
+    }
+
+    /// The average local coefficient of the vertices that can be part of a triangle.
+    pub fn average_local(&self, motif: Motif) -> f64 {
+        let (sum, count) = self
+            .local(motif)
+            .into_iter()
+            .flatten()
+            .fold((0.0, 0), |(s, c), l| (s + l, c + 1));
+        if count == 0 {
+            0.0
+        } else {
+            sum / count as f64
+        }
+    }
+
+    /// Samples two neighbours of `v` that could form a `motif` triangle with it and checks
+    /// whether they do.
+    fn closed<R: Rng>(&self, v: usize, motif: Motif, rng: &mut R) -> bool {
+        let (ins, outs) = (self.predecessors(v), self.successors(v));
+        let (d_in, d_out) = (ins.clone().count(), outs.clone().count());
+        match motif {
+            Motif::Cycle | Motif::Middleman => {
+                // At least half the pairs are distinct, so this doesn't take long
+                let (j, h) = loop {
+                    let j = outs.clone().nth(rng.gen_range(0, d_out)).unwrap();
+                    let h = ins.clone().nth(rng.gen_range(0, d_in)).unwrap();
+                    if j != h {
+                        break (j, h);
+                    }
+                };
+                match motif {
+                    Motif::Cycle => self.graph.has_link(j, h),
+                    _ => self.graph.has_link(h, j),
+                }
+            }
+            Motif::In => {
+                let (j, h) = pair(ins, d_in, rng);
+                self.graph.has_link(j, h)
+            }
+            Motif::Out => {
+                let (j, h) = pair(outs, d_out, rng);
+                self.graph.has_link(j, h)
+            }
+        }
+    }
+
+    /// Estimates [`average_local`](Self::average_local) from `k` sampled pairs of neighbours.
+    pub fn sample<R: Rng>(&self, motif: Motif, k: usize, confidence: f64, rng: &mut R) -> Estimate {
+        let eligible = (0..self.graph.vertices())
+            .filter(|&v| self.possible(v, motif) > 0)
+            .collect::<Vec<_>>();
+        if eligible.is_empty() || k == 0 {
+            return Estimate {
+                value: 0.0,
+                error: 0.0,
+                confidence,
+                samples: 0,
+            };
+        }
+        let closed = (0..k)
+            .filter(|_| {
+                let v = *eligible.choose(rng).unwrap();
+                self.closed(v, motif, rng)
+            })
+            .count();
+        Estimate {
+            value: closed as f64 / k as f64,
+            error: hoeffding_error(k, confidence),
+            confidence,
+            samples: k,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{test_graphs, FromEdges};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn product(a: &[Vec<usize>], b: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let n = a.len();
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| (0..n).map(|k| a[i][k] * b[k][j]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn transpose(a: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        (0..a.len())
+            .map(|i| (0..a.len()).map(|j| a[j][i]).collect())
+            .collect()
+    }
+
+    #[test]
+    fn cycle() {
+        let g = crate::graph![Sorted<CSR> = (3) { 0 => 1; 1 => 2; 2 => 0; 0 => 0; 0 => 1 }];
+        let d = Directed::new(&g);
+        assert_eq!(d.local(Motif::Cycle), vec![Some(1.0); 3]);
+        assert_eq!(d.local(Motif::Middleman), vec![Some(0.0); 3]);
+        assert_eq!(d.local(Motif::In), vec![None; 3]);
+        assert_eq!(d.local(Motif::Out), vec![None; 3]);
+    }
+
+    #[test]
+    fn matches_matrix_formulas() {
+        let n = 40;
+        let edges = test_graphs::random_graph(n, 300, StdRng::seed_from_u64(0xBAD5EED));
+        let mut a = vec![vec![0; n]; n];
+        for &(from, to) in &edges {
+            if from != to {
+                a[from][to] = 1;
+            }
+        }
+        let g = <Sorted<CSR> as FromEdges>::from_edges(n, edges);
+        let d = Directed::new(&g);
+        let at = transpose(&a);
+        let a2 = product(&a, &a);
+        let numerators = [
+            product(&a2, &a),
+            product(&product(&a, &at), &a),
+            product(&at, &a2),
+            product(&a2, &at),
+        ];
+        for v in 0..n {
+            let d_in = at[v].iter().sum::<usize>();
+            let d_out = a[v].iter().sum::<usize>();
+            assert_eq!(d.reciprocated(v), a2[v][v]);
+            assert_eq!(d.possible(v, Motif::Cycle), d_in * d_out - a2[v][v]);
+            for (motif, numerator) in Motif::ALL.iter().zip(&numerators) {
+                assert_eq!(d.triangles(v, *motif), numerator[v][v], "{:?} {}", motif, v);
+            }
+        }
+    }
+
+    #[test]
+    fn sampled() {
+        let edges = test_graphs::random_graph(200, 3000, StdRng::seed_from_u64(0xBAD5EED));
+        let g = <Sorted<CSR> as FromEdges>::from_edges(200, edges);
+        let d = Directed::new(&g);
+        let mut rng = StdRng::seed_from_u64(0xBAD5EED);
+        for &motif in &Motif::ALL {
+            let e = d.sample(motif, 20_000, 0.99, &mut rng);
+            let exact = d.average_local(motif);
+            assert!(
+                (e.value - exact).abs() <= e.error,
+                "{:?}: {:?} {}",
+                motif,
+                e,
+                exact
+            );
+        }
+    }
+}
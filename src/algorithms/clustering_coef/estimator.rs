@@ -0,0 +1,237 @@
+//! Sampling estimators for the unweighted and weighted clustering coefficients.
+//!
+//! Every estimator averages `k` samples that lie in `[0, 1]`, so Hoeffding's inequality bounds
+//! its error: with probability `confidence`, the estimate is within
+//! `√(ln(2 / (1 - confidence)) / 2k)` of the true value, regardless of the graph.
+use super::Wedges;
+use crate::graphs::{RandomAccess, VertexIndex};
+use rand::{distributions::WeightedIndex, prelude::*};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    /// The Hoeffding bound on `|value - exact|`, which holds with probability `confidence`.
+    pub error: f64,
+    pub confidence: f64,
+    pub samples: usize,
+}
+
+/// The Hoeffding bound for the mean of `k` samples in `[0, 1]`.
+pub fn hoeffding_error(k: usize, confidence: f64) -> f64 {
+    ((2.0 / (1.0 - confidence)).ln() / (2 * k) as f64).sqrt()
+}
+
+/// The number of samples needed for the Hoeffding bound to reach `error`.
+pub fn samples_for(error: f64, confidence: f64) -> usize {
+    ((2.0 / (1.0 - confidence)).ln() / (2.0 * error * error)).ceil() as usize
+}
+
+/// Samples wedges, pairs of neighbours of the same vertex, and checks whether they are closed.
+///
+/// Only vertices with at least two distinct neighbours have wedges, so they are the only ones
+/// sampled and averaged over.
+pub struct ClusteringEstimator<'g, G> {
+    g: &'g G,
+    wedges: Wedges<'g, G>,
+    confidence: f64,
+}
+
+impl<'g, G: RandomAccess> ClusteringEstimator<'g, G> {
+    pub fn new(g: &'g G) -> Self {
+        Self {
+            g,
+            wedges: Wedges::new(g),
+            confidence: 0.95,
+        }
+    }
+
+    /// Sets the probability with which the error bounds of the estimates hold.
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// The vertices with at least two distinct neighbours other than themselves.
+    pub fn eligible(&self) -> &[usize] {
+        self.wedges.eligible()
+    }
+
+    /// How many vertices have no wedges.
+    pub fn excluded(&self) -> usize {
+        self.wedges.excluded()
+    }
+
+    fn estimate<F>(&self, k: usize, mut sample: F) -> Estimate
+    where
+        F: FnMut() -> f64,
+    {
+        let empty = self.eligible().is_empty();
+        let (value, error) = if empty || k == 0 {
+            (0.0, 0.0)
+        } else {
+            let sum = (0..k).map(|_| sample()).sum::<f64>();
+            (sum / k as f64, hoeffding_error(k, self.confidence))
+        };
+        Estimate {
+            value,
+            error,
+            confidence: self.confidence,
+            samples: if empty { 0 } else { k },
+        }
+    }
+
+    /// The average local clustering coefficient, sampling vertices uniformly.
+    pub fn average_local<R: Rng>(&self, k: usize, rng: &mut R) -> Estimate {
+        self.estimate(k, || {
+            let v = *self.eligible().choose(rng).unwrap();
+            self.wedges.closed(v, rng) as u8 as f64
+        })
+    }
+
+    /// The global transitivity, the fraction of closed wedges, sampling vertices proportionally
+    /// to their number of wedges, `d(d - 1) / 2`.
+    pub fn transitivity<R: Rng>(&self, k: usize, rng: &mut R) -> Estimate {
+        let wedges = WeightedIndex::new(self.eligible().iter().map(|&v| {
+            let d = self.wedges.degree(v);
+            d * (d - 1) / 2
+        }));
+        match wedges {
+            Ok(wedges) => self.estimate(k, || {
+                let v = self.eligible()[wedges.sample(rng)];
+                self.wedges.closed(v, rng) as u8 as f64
+            }),
+            Err(_) => self.estimate(0, || 0.0),
+        }
+    }
+}
+
+impl<'g, G> ClusteringEstimator<'g, G>
+where
+    G: RandomAccess,
+    G::EdgeWeight: Copy + Into<f64>,
+{
+    fn weight(&self, from: usize, to: usize) -> Option<f64> {
+        self.g
+            .neighbours(from)
+            .iter()
+            .find(|w| w.to.index() == to)
+            .map(|w| w.weight.into())
+    }
+
+    /// The average of Barrat's weighted local coefficient,
+    /// `C(v) = Σ (w(v, j) + w(v, h)) / 2 · a(j, h) / (s(v) (d(v) - 1))`, `s(v)` being the sum of
+    /// the weights of `v`. Weights must be positive.
+    ///
+    /// Picking `j` with probability `w(v, j) / s(v)` and `h` uniformly among the other
+    /// neighbours, `C(v)` is the probability that `j` and `h` are linked.
+    pub fn barrat<R: Rng>(&self, k: usize, rng: &mut R) -> Estimate {
+        self.estimate(k, || {
+            let v = *self.eligible().choose(rng).unwrap();
+            let neighbours = self.g.neighbours(v);
+            let d = self.wedges.degree(v);
+            let position = |i| self.wedges.position(v, i);
+            let i = match WeightedIndex::new((0..d).map(|i| neighbours[position(i)].weight.into()))
+            {
+                Ok(index) => index.sample(rng),
+                Err(_) => return 0.0,
+            };
+            let j = rng.gen_range(0, d - 1);
+            let j = if j >= i { j + 1 } else { j };
+            let (a, b) = (&neighbours[position(i)], &neighbours[position(j)]);
+            self.g.has_link(a.to.index(), b.to.index()) as u8 as f64
+        })
+    }
+
+    /// The average of Onnela's weighted local coefficient,
+    /// `C(v) = Σ (ŵ(v, j) ŵ(v, h) ŵ(j, h))^(1/3) / (d(v) (d(v) - 1))`, `ŵ` being the weights
+    /// divided by the largest one. Weights must not be negative.
+    pub fn onnela<R: Rng>(&self, k: usize, rng: &mut R) -> Estimate {
+        let heaviest = (0..self.g.vertices())
+            .flat_map(|v| self.g.neighbours(v).iter().map(|w| w.weight.into()))
+            .fold(0.0, f64::max);
+        self.estimate(k, || {
+            let v = *self.eligible().choose(rng).unwrap();
+            let (i, j) = self.wedges.sample(v, rng);
+            let neighbours = self.g.neighbours(v);
+            let (a, b) = (&neighbours[i], &neighbours[j]);
+            match self.weight(a.to.index(), b.to.index()) {
+                Some(w) if heaviest > 0.0 => {
+                    (a.weight.into() * b.weight.into() * w).cbrt() / heaviest
+                }
+                _ => 0.0,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::clustering_coef::exact::triangles,
+        graphs::{csr::CSR, matrix::Adjacency, test_graphs, FromEdges},
+    };
+    use rand::rngs::StdRng;
+    use std::collections::BTreeSet;
+    const SEED: u64 = 0xBAD5EED;
+
+    fn symmetric_clustered() -> CSR {
+        let adj = test_graphs::clustered::<Adjacency, _>(500, 8, 3, StdRng::seed_from_u64(SEED));
+        let edges = adj
+            .neighbourhoods()
+            .flat_map(|(f, tos)| tos.flat_map(move |t| vec![(f, t.to), (t.to, f)]))
+            .filter(|(f, t)| f != t)
+            .collect::<BTreeSet<_>>();
+        <CSR as FromEdges>::from_edges(500, edges.into_iter().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn bounds() {
+        approx::assert_abs_diff_eq!(hoeffding_error(1000, 0.95), 0.042_947, epsilon = 1e-6);
+        let k = samples_for(0.01, 0.99);
+        assert!(hoeffding_error(k, 0.99) <= 0.01);
+        assert!(hoeffding_error(k - 1, 0.99) > 0.01);
+    }
+
+    #[test]
+    fn unweighted() {
+        let g = symmetric_clustered();
+        let exact = triangles(&g);
+        let estimator = ClusteringEstimator::new(&g).with_confidence(0.99);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let local = estimator.average_local(20_000, &mut rng);
+        assert!((local.value - exact.average_local()).abs() <= local.error);
+        let transitivity = estimator.transitivity(20_000, &mut rng);
+        assert!((transitivity.value - exact.transitivity()).abs() <= transitivity.error);
+    }
+
+    #[test]
+    fn weighted() {
+        // a triangle 0, 1, 2 and a heavier edge from 0 to 3
+        let g: Adjacency<u32> = crate::graph![Adjacency<u32> = (4) {
+            0 => 1, 1; 1 => 0, 1; 0 => 2, 1; 2 => 0, 1; 1 => 2, 1; 2 => 1, 1;
+            0 => 3, 2; 3 => 0, 2;
+        }];
+        let estimator = ClusteringEstimator::new(&g).with_confidence(0.99);
+        assert_eq!(estimator.eligible(), &[0, 1, 2]);
+        let mut rng = StdRng::seed_from_u64(SEED);
+        // C(0) = 2 / (4 * 2) and C(1) = C(2) = 1
+        let barrat = estimator.barrat(100_000, &mut rng);
+        approx::assert_abs_diff_eq!(barrat.value, 0.75, epsilon = barrat.error);
+        // C(0) = 2 * 0.5 / 6 and C(1) = C(2) = 0.5
+        let onnela = estimator.onnela(100_000, &mut rng);
+        approx::assert_abs_diff_eq!(
+            onnela.value,
+            (1.0 / 6.0 + 1.0) / 3.0,
+            epsilon = onnela.error
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let g: CSR = crate::graph![CSR = (3) { 0 => 1; 1 => 2 }];
+        let e = ClusteringEstimator::new(&g).average_local(100, &mut StdRng::seed_from_u64(SEED));
+        assert_eq!(e.samples, 0);
+        assert_eq!(e.value, 0.0);
+    }
+}
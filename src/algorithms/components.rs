@@ -0,0 +1,240 @@
+//! Weakly and strongly connected components.
+use crate::{
+    graphs::Successors,
+    util::disjoint_set::{DisjointSet, PathCompression, SimpleDisjointSet},
+};
+use std::collections::BTreeMap;
+
+/// A partition of the vertices of a graph into components, numbered `0..count`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    /// The component of each vertex.
+    pub labels: Vec<usize>,
+    pub count: usize,
+}
+
+impl Components {
+    /// The number of vertices in each component.
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.count];
+        self.labels.iter().for_each(|&c| sizes[c] += 1);
+        sizes
+    }
+
+    /// How many components there are of each size.
+    pub fn size_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for size in self.sizes() {
+            *histogram.entry(size).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// The component with the most vertices, the first one if there are several.
+    pub fn largest(&self) -> Option<usize> {
+        let sizes = self.sizes();
+        (0..self.count).rev().max_by_key(|&c| sizes[c])
+    }
+
+    /// The vertices of the component `c`, in increasing order.
+    pub fn vertices(&self, c: usize) -> Vec<usize> {
+        (0..self.labels.len())
+            .filter(|&v| self.labels[v] == c)
+            .collect()
+    }
+
+    /// Whether `labels` splits the vertices into the same components, whatever they are
+    /// numbered as.
+    pub fn same_partition(&self, labels: &[usize]) -> bool {
+        if labels.len() != self.labels.len() {
+            return false;
+        }
+        let mut to_theirs = vec![usize::MAX; self.count];
+        let mut to_ours = BTreeMap::new();
+        self.labels.iter().zip(labels).all(|(&ours, &theirs)| {
+            let mapped = to_ours.entry(theirs).or_insert(ours);
+            if to_theirs[ours] == usize::MAX {
+                to_theirs[ours] = theirs;
+            }
+            *mapped == ours && to_theirs[ours] == theirs
+        })
+    }
+}
+
+/// The weakly connected components of `g`, numbered in the order of their smallest vertex.
+pub fn weakly_connected<G: Successors>(g: &G) -> Components {
+    let n = g.vertices();
+    let mut ds = SimpleDisjointSet::<PathCompression>::new(n);
+    for v in 0..n {
+        for w in g.successors(v) {
+            if !ds.are_connected(v, w) {
+                ds.union(v, w);
+            }
+        }
+    }
+    let mut ids = vec![usize::MAX; n];
+    let mut count = 0;
+    let labels = (0..n)
+        .map(|v| {
+            let root = ds.find(v);
+            if ids[root] == usize::MAX {
+                ids[root] = count;
+                count += 1;
+            }
+            ids[root]
+        })
+        .collect();
+    Components { labels, count }
+}
+
+/// The strongly connected components of `g`, with Tarjan's algorithm.
+///
+/// The depth first search keeps its own stack of the successors left to visit, so it doesn't
+/// overflow the call stack on the long paths of web graphs. Components are numbered in the
+/// order they are completed, which is a reverse topological order of the condensation.
+pub fn strongly_connected<G: Successors>(g: &G) -> Components {
+    const UNVISITED: usize = usize::MAX;
+    let n = g.vertices();
+    let mut index = vec![UNVISITED; n];
+    let mut low = vec![0; n];
+    let mut labels = vec![UNVISITED; n];
+    let mut stack = Vec::new();
+    let mut calls = Vec::new();
+    let mut next = 0;
+    let mut count = 0;
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        index[root] = next;
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        calls.push((root, g.successors(root)));
+        while let Some((v, successors)) = calls.last_mut() {
+            let v = *v;
+            match successors.next() {
+                Some(w) if index[w] == UNVISITED => {
+                    index[w] = next;
+                    low[w] = next;
+                    next += 1;
+                    stack.push(w);
+                    calls.push((w, g.successors(w)));
+                }
+                // Only the vertices still on the stack have no component yet
+                Some(w) => {
+                    if labels[w] == UNVISITED {
+                        low[v] = low[v].min(index[w]);
+                    }
+                }
+                None => {
+                    calls.pop();
+                    if let Some(&(parent, _)) = calls.last() {
+                        low[parent] = low[parent].min(low[v]);
+                    }
+                    if low[v] == index[v] {
+                        loop {
+                            let w = stack.pop().expect("v is on the stack");
+                            labels[w] = count;
+                            if w == v {
+                                break;
+                            }
+                        }
+                        count += 1;
+                    }
+                }
+            }
+        }
+    }
+    Components { labels, count }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{
+        compressed::CompressedCSR, csr::CSR, test_graphs, transform::Transform, webgraph, FromEdges,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn small() {
+        // a cycle 0 -> 1 -> 2 -> 0 leading to 3 <-> 4, and 5 on its own
+        let g =
+            <CSR as FromEdges>::from_edges(6, vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3)]);
+        let weak = weakly_connected(&g);
+        assert_eq!(weak.labels, vec![0, 0, 0, 0, 0, 1]);
+        let strong = strongly_connected(&g);
+        assert_eq!(strong.count, 3);
+        assert!(strong.same_partition(&[7, 7, 7, 2, 2, 9]));
+        assert!(!strong.same_partition(&[7, 7, 7, 2, 2, 2]));
+        assert!(!strong.same_partition(&[7, 7, 7, 7, 7, 9]));
+        // 3 and 4 are completed before the cycle that reaches them
+        assert!(strong.labels[3] < strong.labels[0]);
+        assert_eq!(strong.sizes()[strong.labels[0]], 3);
+        assert_eq!(strong.largest(), Some(strong.labels[0]));
+        assert_eq!(strong.vertices(strong.labels[3]), vec![3, 4]);
+        let histogram = strong.size_histogram();
+        assert_eq!(
+            histogram.into_iter().collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn long_path() {
+        let n = 1_000_000;
+        let g =
+            <CSR as FromEdges>::from_edges(n, (0..n).map(|v| (v, (v + 1) % n)).collect::<Vec<_>>());
+        assert_eq!(strongly_connected(&g).count, 1);
+        let path =
+            <CSR as FromEdges>::from_edges(n, (1..n).map(|v| (v - 1, v)).collect::<Vec<_>>());
+        let strong = strongly_connected(&path);
+        assert_eq!(strong.count, n);
+        assert_eq!(strong.labels[0], n - 1);
+        assert_eq!(weakly_connected(&path).count, 1);
+    }
+
+    #[test]
+    fn symmetric_graphs_agree() {
+        let g = <CSR as FromEdges>::from_edges(
+            500,
+            test_graphs::random_graph(500, 400, StdRng::seed_from_u64(0xBAD5EED)),
+        );
+        let weak = weakly_connected(&g);
+        let symmetric = g.symmetrize(|a, _| a);
+        assert!(strongly_connected(&symmetric).same_partition(&weak.labels));
+        assert_eq!(weakly_connected(&symmetric), weak);
+        let compressed = CompressedCSR::from_csr(&g);
+        assert!(strongly_connected(&compressed).same_partition(&strongly_connected(&g).labels));
+        assert_eq!(
+            weak.sizes().iter().sum::<usize>(),
+            500,
+            "every vertex is in a component"
+        );
+    }
+
+    /// Checks the components against the ones WebGraph computed for cnr-2000, if the dataset
+    /// was downloaded to `graphs/cnr-2000`.
+    #[test]
+    #[ignore]
+    fn cnr_2000() {
+        let basename =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("graphs/cnr-2000/cnr-2000");
+        if !basename.with_extension("graph").exists() {
+            return;
+        }
+        let g = webgraph::load::<u32, _>(&basename).unwrap();
+        let strong = strongly_connected(&g);
+        assert!(strong.same_partition(&webgraph::load_scc(&basename).unwrap()));
+        let mut sizes = strong.sizes();
+        let mut expected = webgraph::load_scc_sizes(&basename).unwrap();
+        sizes.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(sizes, expected);
+
+        let weak = weakly_connected(&g);
+        let symmetric = g.symmetrize(|a, _| a);
+        assert!(strongly_connected(&symmetric).same_partition(&weak.labels));
+    }
+}
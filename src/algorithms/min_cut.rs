@@ -1,5 +1,5 @@
 use crate::{
-    graphs::{EdgeListGraph, WEdge},
+    graphs::{EdgeListGraph, VertexIndex},
     util::disjoint_set::*,
 };
 use itertools::Itertools;
@@ -8,8 +8,8 @@ use rand::{
     thread_rng,
 };
 
-fn contract<E, D>(
-    edges: &mut [WEdge<E>],
+fn contract<Ix: VertexIndex, E, D>(
+    edges: &mut [(Ix, Ix, E)],
     ds: &mut D,
     comp: usize,
     cur_node: &mut usize,
@@ -22,8 +22,8 @@ where
     while cur < edges.len() && ds.components() > comp {
         let i = UniformInt::<usize>::sample_single(cur, edges.len(), &mut rng);
         edges.swap(cur, i);
-        if !ds.are_connected(edges[cur].0, edges[cur].1) {
-            ds.union(edges[cur].0, edges[cur].1);
+        if !ds.are_connected(edges[cur].0.index(), edges[cur].1.index()) {
+            ds.union(edges[cur].0.index(), edges[cur].1.index());
         }
         cur += 1;
     }
@@ -37,15 +37,19 @@ where
     if comp == 2 {
         edges
             .iter()
-            .filter(|e| !ds.are_connected(e.0, e.1))
-            .map(|(f, t, _)| (*f, *t))
+            .filter(|e| !ds.are_connected(e.0.index(), e.1.index()))
+            .map(|(f, t, _)| (f.index(), t.index()))
             .collect()
     } else {
         Default::default()
     }
 }
 
-fn min_cut<E, D>(edges: &mut [WEdge<E>], mut ds: D, mut current_node: usize) -> Vec<(usize, usize)>
+fn min_cut<Ix: VertexIndex, E, D>(
+    edges: &mut [(Ix, Ix, E)],
+    mut ds: D,
+    mut current_node: usize,
+) -> Vec<(usize, usize)>
 where
     D: DisjointSet + Clone,
 {
@@ -84,8 +88,8 @@ where
         .unwrap()
 }
 
-fn fast_min_cut<E, F>(
-    edges: &mut [WEdge<E>],
+fn fast_min_cut<Ix: VertexIndex, E, F>(
+    edges: &mut [(Ix, Ix, E)],
     ds: &mut UndoDisjointSet<F>,
     current_node: usize,
 ) -> Vec<(usize, usize)>
@@ -135,7 +139,7 @@ where
 
 pub mod count {
     use crate::{
-        graphs::{EdgeListGraph, WEdge},
+        graphs::{EdgeListGraph, VertexIndex},
         util::disjoint_set::{DisjointSet, FindMode, SimpleDisjointSet, UndoDisjointSet},
     };
     use itertools::Itertools;
@@ -144,8 +148,8 @@ pub mod count {
         thread_rng,
     };
 
-    fn contract_count<E, D>(
-        edges: &mut [WEdge<E>],
+    fn contract_count<Ix: VertexIndex, E, D>(
+        edges: &mut [(Ix, Ix, E)],
         ds: &mut D,
         comp: usize,
         cur_node: &mut usize,
@@ -158,8 +162,8 @@ pub mod count {
         while cur < edges.len() && ds.components() > comp {
             let i = UniformInt::<usize>::sample_single(cur, edges.len(), &mut rng);
             edges.swap(cur, i);
-            if !ds.are_connected(edges[cur].0, edges[cur].1) {
-                ds.union(edges[cur].0, edges[cur].1);
+            if !ds.are_connected(edges[cur].0.index(), edges[cur].1.index()) {
+                ds.union(edges[cur].0.index(), edges[cur].1.index());
             }
             cur += 1;
         }
@@ -171,13 +175,20 @@ pub mod count {
             edges.iter().map(|e| (e.0, e.1)).format(",")
         );
         if comp == 2 {
-            edges.iter().filter(|e| !ds.are_connected(e.0, e.1)).count()
+            edges
+                .iter()
+                .filter(|e| !ds.are_connected(e.0.index(), e.1.index()))
+                .count()
         } else {
             Default::default()
         }
     }
 
-    fn min_cut_count<E, D>(edges: &mut [WEdge<E>], mut ds: D, current_node: usize) -> usize
+    fn min_cut_count<Ix: VertexIndex, E, D>(
+        edges: &mut [(Ix, Ix, E)],
+        mut ds: D,
+        current_node: usize,
+    ) -> usize
     where
         D: DisjointSet + Clone,
     {
@@ -213,8 +224,8 @@ pub mod count {
             .unwrap()
     }
 
-    fn fast_min_cut_count<E, F>(
-        edges: &mut [WEdge<E>],
+    fn fast_min_cut_count<Ix: VertexIndex, E, F>(
+        edges: &mut [(Ix, Ix, E)],
         ds: &mut UndoDisjointSet<F>,
         current_node: usize,
     ) -> usize
@@ -311,7 +322,7 @@ mod test {
                     }
                     let succ = check_min_cut(&min_cut, || {
                         super::fast_karger_stein::<_, $t>(
-                            &mut EdgeList::from_edges(20, g.clone())
+                            &mut <EdgeList>::from_edges(20, g.clone())
                         )
                     });
                     assert!(succ > SUCCSESS_RATE, "Got it right {} times", succ)
@@ -395,7 +406,7 @@ mod test {
                         }
                         let succ = check_min_cut(min_cut.len(), || {
                             fast_karger_stein_count::<_, $t>(
-                                &mut EdgeList::from_edges(20, g.clone())
+                                &mut <EdgeList>::from_edges(20, g.clone())
                             )
                         });
                         assert!(succ > SUCCSESS_RATE, "Got it right {} times", succ)
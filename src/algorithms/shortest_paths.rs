@@ -0,0 +1,296 @@
+//! Single source shortest paths over weighted graphs.
+//!
+//! Weights only need to be ordered and added, with `W::default()` as the length of the empty
+//! path, so both integers and wrappers of floats with a total order work.
+use crate::graphs::WSuccessors;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::{self, Display},
+    ops::Add,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths<W> {
+    pub source: usize,
+    /// The length of a shortest path to each vertex, or `None` if it isn't reachable.
+    pub distances: Vec<Option<W>>,
+    /// The vertex before each one on a shortest path from the source.
+    pub parents: Vec<Option<usize>>,
+}
+
+impl<W: Copy> ShortestPaths<W> {
+    fn new(n: usize, source: usize) -> Self
+    where
+        W: Default,
+    {
+        let mut distances = vec![None; n];
+        distances[source] = Some(W::default());
+        Self {
+            source,
+            distances,
+            parents: vec![None; n],
+        }
+    }
+
+    /// The vertices of a shortest path from the source to `to`, both included.
+    pub fn path_to(&self, to: usize) -> Option<Vec<usize>> {
+        self.distances[to]?;
+        let mut path = vec![to];
+        let mut v = to;
+        while let Some(p) = self.parents[v] {
+            path.push(p);
+            v = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Replaces the distance to `to` if going through `from` is shorter.
+    fn relax(&mut self, from: usize, to: usize, weight: W) -> bool
+    where
+        W: Ord + Add<Output = W>,
+    {
+        let distance = match self.distances[from] {
+            Some(d) => d + weight,
+            None => return false,
+        };
+        if self.distances[to].is_none_or(|d| distance < d) {
+            self.distances[to] = Some(distance);
+            self.parents[to] = Some(from);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Dijkstra's algorithm with a binary heap. Weights must not be negative.
+pub fn dijkstra<G, W>(g: &G, source: usize) -> ShortestPaths<W>
+where
+    G: WSuccessors<EdgeWeight = W>,
+    W: Ord + Add<Output = W> + Copy + Default,
+{
+    let mut paths = ShortestPaths::new(g.vertices(), source);
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((W::default(), source)));
+    while let Some(Reverse((d, v))) = heap.pop() {
+        // Stale entries are left in the heap when a shorter path is found
+        if paths.distances[v] != Some(d) {
+            continue;
+        }
+        for w in g.weighted_successors(v) {
+            if paths.relax(v, w.to, w.weight) {
+                heap.push(Reverse((d + w.weight, w.to)));
+            }
+        }
+    }
+    paths
+}
+
+/// Non negative integer weights, which [`dial`] uses to pick the bucket of each vertex.
+pub trait Integer: Copy {
+    fn to_usize(self) -> usize;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {
+            fn to_usize(self) -> usize {
+                self as usize
+            }
+        })*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, usize);
+
+/// The heaviest arc [`dial`] makes buckets for, falling back to [`dijkstra`] past it.
+pub const DIAL_MAX_WEIGHT: usize = 1 << 16;
+
+/// Dial's algorithm: Dijkstra with a circular array of `C + 1` buckets instead of a heap, `C`
+/// being the heaviest arc. Runs in `O(m + nC)` time, which beats the heap for small weights.
+///
+/// If `C` is larger than [`DIAL_MAX_WEIGHT`], the buckets would take more memory and time than
+/// the heap, so this runs [`dijkstra`] instead.
+pub fn dial<G, W>(g: &G, source: usize) -> ShortestPaths<W>
+where
+    G: WSuccessors<EdgeWeight = W>,
+    W: Integer + Ord + Add<Output = W> + Default,
+{
+    let n = g.vertices();
+    let heaviest = (0..n)
+        .flat_map(|v| g.weighted_successors(v).map(|w| w.weight.to_usize()))
+        .max()
+        .unwrap_or(0);
+    if heaviest > DIAL_MAX_WEIGHT {
+        return dijkstra(g, source);
+    }
+    let width = heaviest + 1;
+    let mut paths = ShortestPaths::new(n, source);
+    let mut buckets = vec![Vec::new(); width];
+    let mut done = vec![false; n];
+    buckets[0].push(source);
+    let mut pending = 1;
+    let mut at = 0;
+    while pending > 0 {
+        let bucket = at % width;
+        while let Some(v) = buckets[bucket].pop() {
+            pending -= 1;
+            if done[v] || paths.distances[v].map(Integer::to_usize) != Some(at) {
+                continue;
+            }
+            done[v] = true;
+            for w in g.weighted_successors(v) {
+                if paths.relax(v, w.to, w.weight) {
+                    buckets[(at + w.weight.to_usize()) % width].push(w.to);
+                    pending += 1;
+                }
+            }
+        }
+        at += 1;
+    }
+    paths
+}
+
+/// A cycle of negative length reachable from the source, as the list of its vertices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle(pub Vec<usize>);
+
+impl Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "negative cycle through vertices {:?}", self.0)
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+/// The Bellman–Ford algorithm, which allows negative weights but fails if a negative cycle is
+/// reachable from the source.
+pub fn bellman_ford<G, W>(g: &G, source: usize) -> Result<ShortestPaths<W>, NegativeCycle>
+where
+    G: WSuccessors<EdgeWeight = W>,
+    W: Ord + Add<Output = W> + Copy + Default,
+{
+    let n = g.vertices();
+    let mut paths = ShortestPaths::new(n, source);
+    let relax_all = |paths: &mut ShortestPaths<W>| {
+        let mut last = None;
+        for v in 0..n {
+            for w in g.weighted_successors(v) {
+                if paths.relax(v, w.to, w.weight) {
+                    last = Some(w.to);
+                }
+            }
+        }
+        last
+    };
+    for _ in 1..n {
+        if relax_all(&mut paths).is_none() {
+            return Ok(paths);
+        }
+    }
+    match relax_all(&mut paths) {
+        None => Ok(paths),
+        Some(mut v) => {
+            // Walking back n parents from a vertex still being relaxed lands on the cycle
+            for _ in 0..n {
+                v = paths.parents[v].expect("relaxed vertices have a parent");
+            }
+            let mut cycle = vec![v];
+            let mut u = paths.parents[v].unwrap();
+            while u != v {
+                cycle.push(u);
+                u = paths.parents[u].unwrap();
+            }
+            cycle.reverse();
+            Err(NegativeCycle(cycle))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{
+        csr::CSR, edge_list::EdgeList, matrix::Adjacency, test_graphs, WFromEdges,
+    };
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn weighted() -> Adjacency<u32> {
+        crate::graph![Adjacency<u32> = (6) {
+            0 => 1, 7; 0 => 2, 9; 0 => 5, 14;
+            1 => 2, 10; 1 => 3, 15;
+            2 => 3, 11; 2 => 5, 2;
+            3 => 4, 6;
+            5 => 4, 9;
+        }]
+    }
+
+    #[test]
+    fn small() {
+        let g = weighted();
+        let paths = dijkstra(&g, 0);
+        assert_eq!(
+            paths.distances,
+            vec![Some(0), Some(7), Some(9), Some(20), Some(20), Some(11)]
+        );
+        assert_eq!(paths.path_to(4), Some(vec![0, 2, 5, 4]));
+        assert_eq!(paths.path_to(0), Some(vec![0]));
+        assert_eq!(dial(&g, 0).distances, paths.distances);
+        assert_eq!(bellman_ford(&g, 0).unwrap().distances, paths.distances);
+        // nothing reaches back to 0
+        assert_eq!(dijkstra(&g, 4).path_to(0), None);
+    }
+
+    #[test]
+    fn variants_agree() {
+        let mut rng = StdRng::seed_from_u64(0xBAD5EED);
+        let edges = test_graphs::random_graph(200, 1500, &mut rng)
+            .into_iter()
+            .map(|(from, to)| (from, to, rng.gen_range(0u32, 20)))
+            .collect::<Vec<_>>();
+        let g: CSR<u32> = WFromEdges::from_edges(200, edges.clone());
+        let narrow: CSR<u32, u32> = WFromEdges::from_edges(200, edges.clone());
+        let list: EdgeList<u32> = WFromEdges::from_edges(200, edges);
+        for source in 0..10 {
+            let expected = dijkstra(&g, source).distances;
+            assert_eq!(dial(&g, source).distances, expected);
+            assert_eq!(bellman_ford(&g, source).unwrap().distances, expected);
+            assert_eq!(dijkstra(&narrow, source).distances, expected);
+            assert_eq!(dijkstra(&list, source).distances, expected);
+        }
+    }
+
+    #[test]
+    fn heavy_arcs() {
+        let g: Adjacency<u64> = crate::graph![Adjacency<u64> = (4) {
+            0 => 1, u64::MAX / 4; 0 => 2, 3; 2 => 1, 4; 1 => 3, 1 << 40;
+        }];
+        let paths = dial(&g, 0);
+        assert_eq!(paths, dijkstra(&g, 0));
+        assert_eq!(paths.distances[3], Some(7 + (1 << 40)));
+    }
+
+    #[test]
+    fn negative_weights() {
+        let g: Adjacency<i32> = crate::graph![Adjacency<i32> = (4) {
+            0 => 1, 4; 0 => 2, 2; 2 => 1, -3; 1 => 3, 1;
+        }];
+        let paths = bellman_ford(&g, 0).unwrap();
+        assert_eq!(paths.distances, vec![Some(0), Some(-1), Some(2), Some(0)]);
+        assert_eq!(paths.path_to(3), Some(vec![0, 2, 1, 3]));
+    }
+
+    #[test]
+    fn negative_cycle() {
+        let g: Adjacency<i32> = crate::graph![Adjacency<i32> = (5) {
+            0 => 1, 1; 1 => 2, 1; 2 => 3, -4; 3 => 1, 2; 3 => 4, 1;
+        }];
+        let NegativeCycle(mut cycle) = bellman_ford(&g, 0).unwrap_err();
+        cycle.sort_unstable();
+        assert_eq!(cycle, vec![1, 2, 3]);
+        // the cycle can't be reached from 4
+        assert!(bellman_ford(&g, 4).is_ok());
+    }
+}
@@ -1,7 +1,16 @@
+pub mod compressed;
+pub mod convert;
 pub mod csr;
 pub mod edge_list;
+pub mod io;
+pub mod labelled;
+pub mod mapped;
 pub mod matrix;
+pub mod sorted;
+pub mod subgraph;
 pub mod test_graphs;
+pub mod transform;
+pub mod webgraph;
 
 pub trait Graph {
     type EdgeWeight;
@@ -39,11 +48,13 @@ where
     }
 }
 
+/// A graph stored as its list of arcs, with their endpoints as [`VertexIndex`]es.
 pub trait EdgeListGraph: Graph {
-    type Edges: IntoIterator<Item = WEdge<Self::EdgeWeight>>;
+    type Index: VertexIndex;
+    type Edges: IntoIterator<Item = (Self::Index, Self::Index, Self::EdgeWeight)>;
 
-    fn as_edges(&self) -> &[WEdge<Self::EdgeWeight>];
-    fn as_edges_mut(&mut self) -> &mut [WEdge<Self::EdgeWeight>];
+    fn as_edges(&self) -> &[(Self::Index, Self::Index, Self::EdgeWeight)];
+    fn as_edges_mut(&mut self) -> &mut [(Self::Index, Self::Index, Self::EdgeWeight)];
     fn into_edges(self) -> Self::Edges;
 }
 
@@ -65,28 +76,139 @@ where
     }
 }
 
-pub trait RandomAccess: Graph {
-    /// Returns the vertices of the graph connected to `from`
-    fn neighbours(&self, from: usize) -> &[To<Self::EdgeWeight>];
+/// Graphs that can look up an arc, whether or not they keep the neighbours in a slice.
+pub trait HasLink: Graph {
     /// Checks if two vertices are connected
     fn has_link(&self, from: usize, to: usize) -> bool;
 }
 
-impl<T> RandomAccess for T
+pub trait RandomAccess: HasLink {
+    /// The integer type the vertex ids of the arcs are stored as.
+    type Index: VertexIndex;
+
+    /// Returns the vertices of the graph connected to `from`
+    fn neighbours(&self, from: usize) -> &[To<Self::EdgeWeight, Self::Index>];
+}
+
+/// Graphs that can list the successors of a vertex, whether or not they keep them in a slice.
+///
+/// Every representation implements it, so the traversals written against it run on any of them.
+pub trait Successors: Graph {
+    type Successors<'a>: Iterator<Item = usize>
+    where
+        Self: 'a;
+
+    /// The vertices `from` has an arc to, once per arc.
+    fn successors(&self, from: usize) -> Self::Successors<'_>;
+}
+
+impl<G: RandomAccess> Successors for G {
+    type Successors<'a>
+        = std::iter::Map<
+            std::slice::Iter<'a, To<G::EdgeWeight, G::Index>>,
+            fn(&To<G::EdgeWeight, G::Index>) -> usize,
+        >
+    where
+        Self: 'a;
+
+    fn successors(&self, from: usize) -> Self::Successors<'_> {
+        self.neighbours(from).iter().map(|w| w.to.index())
+    }
+}
+
+/// [`Successors`] together with the weights of the arcs to them.
+///
+/// The weights are handed out by value, as some representations don't keep them in memory
+/// with the right alignment, or at all.
+pub trait WSuccessors: Successors {
+    type WSuccessors<'a>: Iterator<Item = To<Self::EdgeWeight>>
+    where
+        Self: 'a;
+
+    /// The arcs leaving `from`, in the same order as [`successors`](Successors::successors).
+    fn weighted_successors(&self, from: usize) -> Self::WSuccessors<'_>;
+}
+
+impl<G> WSuccessors for G
 where
-    T: Graph + std::ops::Index<usize, Output = [To<Self::EdgeWeight>]>,
+    G: RandomAccess,
+    G::EdgeWeight: Clone,
 {
-    fn neighbours(&self, from: usize) -> &[To<Self::EdgeWeight>] {
-        &self[from]
-    }
+    type WSuccessors<'a>
+        = std::iter::Map<
+            std::slice::Iter<'a, To<G::EdgeWeight, G::Index>>,
+            fn(&To<G::EdgeWeight, G::Index>) -> To<G::EdgeWeight>,
+        >
+    where
+        Self: 'a;
 
-    fn has_link(&self, from: usize, to: usize) -> bool {
-        self[from].iter().any(|n| n.to == to)
+    fn weighted_successors(&self, from: usize) -> Self::WSuccessors<'_> {
+        self.neighbours(from).iter().map(|w| To {
+            to: w.to.index(),
+            weight: w.weight.clone(),
+        })
     }
 }
 
+/// The integer type the vertex ids of the arcs of a graph are stored as.
+///
+/// Graphs use `usize` by default. Graphs with less than `2^32` vertices can use `u32`, halving
+/// the memory taken by their arcs; [`RandomAccess`] hands out the stored ids, which
+/// [`index`](VertexIndex::index) widens back.
+pub trait VertexIndex:
+    Copy + Ord + std::hash::Hash + std::fmt::Debug + Default + Send + Sync
+{
+    /// Converts a vertex id, panicking if it doesn't fit.
+    fn new(v: usize) -> Self;
+    fn index(self) -> usize;
+}
+
+macro_rules! impl_vertex_index {
+    ($($t:ty),*) => {
+        $(
+        impl VertexIndex for $t {
+            #[inline]
+            fn new(v: usize) -> Self {
+                use std::convert::TryFrom;
+                <$t>::try_from(v).unwrap_or_else(|_| {
+                    panic!("vertex {} doesn't fit in a {}", v, stringify!($t))
+                })
+            }
+
+            #[inline]
+            fn index(self) -> usize {
+                self as usize
+            }
+        }
+        )*
+    };
+}
+impl_vertex_index!(u16, u32, u64, usize);
 
-impl<'g, G> Graph for &'g G
+/// Implements [`RandomAccess`] and [`HasLink`] for a graph indexed by vertex, whatever the
+/// width of its vertex ids.
+macro_rules! impl_indexed_graph {
+    ($g:ident) => {
+        impl<E, Ix: $crate::graphs::VertexIndex> $crate::graphs::RandomAccess for $g<E, Ix> {
+            type Index = Ix;
+
+            fn neighbours(&self, from: usize) -> &[$crate::graphs::To<E, Ix>] {
+                &self[from]
+            }
+        }
+
+        impl<E, Ix: $crate::graphs::VertexIndex> $crate::graphs::HasLink for $g<E, Ix> {
+            fn has_link(&self, from: usize, to: usize) -> bool {
+                self[from]
+                    .iter()
+                    .any(|n| $crate::graphs::VertexIndex::index(n.to) == to)
+            }
+        }
+    };
+}
+pub(crate) use impl_indexed_graph;
+
+impl<G> Graph for &G
 where
     G: Graph,
 {
@@ -118,8 +240,8 @@ macro_rules! graph {
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub struct To<E> {
-    pub to: usize,
+pub struct To<E, Ix = usize> {
+    pub to: Ix,
     pub weight: E,
 }
 
@@ -137,8 +259,10 @@ impl From<usize> for To<()> {
 
 #[cfg(test)]
 mod test {
-    use crate::graph;
-    use crate::graphs::{matrix::Adjacency, Graph};
+    use crate::graphs::{
+        compressed::CompressedCSR, csr::CSR, edge_list::EdgeList, matrix::Adjacency,
+        sorted::Sorted, Graph, Successors, To, VertexIndex, WFromEdges, WMutable, WSuccessors,
+    };
 
     #[test]
     fn test_macro() {
@@ -162,4 +286,61 @@ mod test {
         assert_eq!(graph[1].iter().map(|i| i.weight).sum::<i32>(), 0);
         assert_eq!(graph[2].iter().map(|i| i.weight).sum::<i32>(), 25);
     }
+
+    #[test]
+    fn narrow_indexes() {
+        assert_eq!(std::mem::size_of::<To<(), u32>>(), 4);
+        let mut g = Adjacency::<i32, u16>::default();
+        g.add_weighed_link(1, 2, 7);
+        g.add_weighed_link(1, 0, 8);
+        g.add_vertex(2);
+        assert_eq!(g.vertices(), 3);
+        assert_eq!(g.successors(1).collect::<Vec<_>>(), vec![2, 0]);
+        assert_eq!(g[1][0].to.index(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "vertex 65536 doesn't fit in a u16")]
+    fn index_overflow() {
+        u16::new(1 << 16);
+    }
+
+    #[test]
+    fn successors_agree() {
+        let arcs = vec![
+            (2, 0, 1),
+            (0, 3, 2),
+            (2, 1, 3),
+            (0, 1, 4),
+            (3, 3, 5),
+            (2, 0, 6),
+        ];
+        let csr: CSR<i32> = WFromEdges::from_edges(4, arcs.clone());
+        let narrow: CSR<i32, u32> = WFromEdges::from_edges(4, arcs.clone());
+        let adjacency: Adjacency<i32, u16> = WFromEdges::from_edges(4, arcs.clone());
+        let list: EdgeList<i32> = WFromEdges::from_edges(4, arcs.clone());
+        fn all<G: WSuccessors>(g: &G) -> Vec<Vec<To<G::EdgeWeight>>> {
+            (0..g.vertices())
+                .map(|v| g.weighted_successors(v).collect())
+                .collect()
+        }
+        let expected = all(&csr);
+        assert_eq!(
+            expected[2].iter().map(|w| w.weight).collect::<Vec<_>>(),
+            vec![1, 3, 6]
+        );
+        assert_eq!(all(&narrow), expected);
+        assert_eq!(all(&adjacency), expected);
+        assert_eq!(all(&list), expected);
+
+        let unweighted = CSR::<()>::from_edges(4, arcs.iter().map(|&(f, t, _)| (f, t, ())));
+        let compressed = CompressedCSR::from_csr(&unweighted);
+        let sorted = Sorted::new(unweighted.clone());
+        for v in 0..4 {
+            let mut successors = list.successors(v).collect::<Vec<_>>();
+            successors.sort_unstable();
+            assert_eq!(compressed.successors(v).collect::<Vec<_>>(), successors);
+            assert_eq!(sorted.successors(v).collect::<Vec<_>>(), successors);
+        }
+    }
 }
@@ -0,0 +1,259 @@
+//! A CSR whose neighbour lists are sorted and gap encoded.
+//!
+//! Each list is stored as a sequence of variable length integers, seven bits per byte, the
+//! highest bit of a byte being set when more bytes follow:
+//! ```text
+//! outdegree
+//! first successor - x     (zig-zag encoded, as it may be negative)
+//! gaps between the following successors
+//! ```
+//! Neighbours tend to be close to each other and to `x`, so most arcs take one or two bytes
+//! instead of the eight of a `usize`. The lists can only be read sequentially, so the graph
+//! doesn't implement [`RandomAccess`](super::RandomAccess), only [`Successors`] and
+//! [`HasLink`].
+use super::{
+    csr::CSR, Graph, HasLink, Successors, To, VertexIndex, WEdge, WFromEdges, WSuccessors,
+};
+use itertools::Itertools;
+use std::{
+    fmt::{self, Debug},
+    iter::Map,
+};
+
+/// An unweighted graph with compressed neighbour lists.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CompressedCSR {
+    bytes: Box<[u8]>,
+    /// The position in `bytes` where the list of each vertex starts, and one past the last.
+    offsets: Box<[usize]>,
+    n_edges: usize,
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut x: usize) {
+    while x >= 0x80 {
+        bytes.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    bytes.push(x as u8);
+}
+
+#[inline]
+fn read_varint(bytes: &[u8], position: &mut usize) -> usize {
+    let mut x = 0;
+    let mut shift = 0;
+    loop {
+        let b = bytes[*position];
+        *position += 1;
+        x |= ((b & 0x7f) as usize) << shift;
+        if b < 0x80 {
+            return x;
+        }
+        shift += 7;
+    }
+}
+
+impl CompressedCSR {
+    /// Compresses the neighbour lists of `g`, dropping its weights and keeping repeated arcs.
+    pub fn from_csr<E, Ix: VertexIndex>(g: &CSR<E, Ix>) -> Self {
+        let mut bytes = Vec::new();
+        let mut offsets = Vec::with_capacity(g.vertices() + 1);
+        let mut row = Vec::new();
+        for (x, neighbours) in g.neighbourhoods().enumerate() {
+            offsets.push(bytes.len());
+            row.clear();
+            row.extend(neighbours.iter().map(|w| w.to.index()));
+            row.sort_unstable();
+            write_varint(&mut bytes, row.len());
+            if let Some(&first) = row.first() {
+                let delta = first as isize - x as isize;
+                write_varint(
+                    &mut bytes,
+                    ((delta << 1) ^ (delta >> (isize::BITS - 1))) as usize,
+                );
+            }
+            for w in row.windows(2) {
+                write_varint(&mut bytes, w[1] - w[0]);
+            }
+        }
+        offsets.push(bytes.len());
+        Self {
+            bytes: bytes.into_boxed_slice(),
+            offsets: offsets.into_boxed_slice(),
+            n_edges: g.edges(),
+        }
+    }
+
+    /// The successors of `x`, in increasing order.
+    pub fn neighbours(&self, x: usize) -> Neighbours<'_> {
+        let mut position = self.offsets[x];
+        let remaining = read_varint(&self.bytes, &mut position);
+        Neighbours {
+            bytes: &self.bytes,
+            position,
+            remaining,
+            last: None,
+            x,
+        }
+    }
+
+    pub fn degree(&self, x: usize) -> usize {
+        let mut position = self.offsets[x];
+        read_varint(&self.bytes, &mut position)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = usize> {
+        0..self.vertices()
+    }
+
+    /// The number of bytes used by the neighbour lists and their offsets.
+    pub fn size_in_bytes(&self) -> usize {
+        self.bytes.len() + self.offsets.len() * std::mem::size_of::<usize>()
+    }
+}
+
+impl Graph for CompressedCSR {
+    type EdgeWeight = ();
+    fn vertices(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn edges(&self) -> usize {
+        self.n_edges
+    }
+}
+
+impl WFromEdges for CompressedCSR {
+    fn from_edges<I, Iter>(n: usize, list: I) -> Self
+    where
+        I: IntoIterator<IntoIter = Iter, Item = WEdge<()>>,
+        Iter: ExactSizeIterator<Item = WEdge<()>>,
+    {
+        Self::from_csr(&CSR::<()>::from_edges(n, list))
+    }
+}
+
+impl Successors for CompressedCSR {
+    type Successors<'a> = Neighbours<'a>;
+
+    fn successors(&self, from: usize) -> Neighbours<'_> {
+        self.neighbours(from)
+    }
+}
+
+impl HasLink for CompressedCSR {
+    /// Decodes the successors of `from` up to `to`, as they are sorted.
+    fn has_link(&self, from: usize, to: usize) -> bool {
+        self.neighbours(from)
+            .take_while(|&w| w <= to)
+            .any(|w| w == to)
+    }
+}
+
+impl WSuccessors for CompressedCSR {
+    type WSuccessors<'a> = Map<Neighbours<'a>, fn(usize) -> To<()>>;
+
+    fn weighted_successors(&self, from: usize) -> Self::WSuccessors<'_> {
+        self.neighbours(from).map(|to| To { to, weight: () })
+    }
+}
+
+/// Decodes the neighbour list of a vertex of a [`CompressedCSR`].
+#[derive(Clone)]
+pub struct Neighbours<'g> {
+    bytes: &'g [u8],
+    position: usize,
+    remaining: usize,
+    last: Option<usize>,
+    x: usize,
+}
+
+impl Iterator for Neighbours<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let code = read_varint(self.bytes, &mut self.position);
+        let next = match self.last {
+            Some(last) => last + code,
+            None => (self.x as isize + ((code >> 1) as isize ^ -((code & 1) as isize))) as usize,
+        };
+        self.last = Some(next);
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Neighbours<'_> {}
+
+impl Debug for CompressedCSR {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.nodes()
+            .try_for_each(|x| writeln!(f, "{:?}: {:?}", x, self.neighbours(x).format(" -> ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::apl::{bfs, hyper_ball::hyper_ball},
+        graphs::{test_graphs, FromEdges},
+        util::hyper_counters::{HyperLogLog, B},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn varints() {
+        let values = [0, 1, 127, 128, 300, 1 << 35, usize::MAX];
+        let mut bytes = Vec::new();
+        values.iter().for_each(|&v| write_varint(&mut bytes, v));
+        assert_eq!(bytes.len(), 1 + 1 + 1 + 2 + 2 + 6 + 10);
+        let mut position = 0;
+        for &v in &values {
+            assert_eq!(read_varint(&bytes, &mut position), v);
+        }
+    }
+
+    #[test]
+    fn same_graph() {
+        let n = 1000;
+        let edges = test_graphs::random_graph(n, 20_000, StdRng::seed_from_u64(0xBAD5EED));
+        let mut csr = <CSR as FromEdges>::from_edges(n, edges);
+        let g = CompressedCSR::from_csr(&csr);
+        csr.sort_neighbours();
+        assert_eq!(g.vertices(), n);
+        assert_eq!(g.edges(), csr.edges());
+        for x in g.nodes() {
+            assert_eq!(g.degree(x), csr[x].len());
+            assert!(g.neighbours(x).eq(csr.neighbours(x).map(|w| w.to)));
+        }
+        assert!(g.size_in_bytes() < csr.edges() * std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn narrow_indexes() {
+        let n = 1000;
+        let edges = test_graphs::random_graph(n, 20_000, StdRng::seed_from_u64(0xBAD5EED));
+        let narrow = <CSR<(), u32> as FromEdges>::from_edges(n, edges.clone());
+        let wide = <CSR as FromEdges>::from_edges(n, edges);
+        let g = CompressedCSR::from_csr(&narrow);
+        assert_eq!(g.edges(), wide.edges());
+        assert_eq!(g.bytes, CompressedCSR::from_csr(&wide).bytes);
+    }
+
+    #[test]
+    fn algorithms() {
+        let csr = test_graphs::graph_one::<CSR>();
+        let g = CompressedCSR::from_csr(&csr);
+        assert_eq!(bfs::path_lengths(&g), bfs::path_lengths(&csr));
+        assert_eq!(bfs::bit_parallel_path_lengths(&g), bfs::path_lengths(&csr));
+        let counters = || vec![HyperLogLog::new_with_seed(B::B4, 0xBAD5EED); 10].into_boxed_slice();
+        assert_eq!(hyper_ball(&g, counters()), hyper_ball(&csr, counters()));
+    }
+}
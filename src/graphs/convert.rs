@@ -0,0 +1,213 @@
+//! Conversions between [`EdgeList`], [`Adjacency`] and [`CSR`].
+//!
+//! They take the graph by value and move its weights, keeping every arc, repeated ones
+//! included, and the order of the arcs leaving each vertex. Each one runs in `O(n + m)` time.
+use super::{csr::CSR, edge_list::EdgeList, matrix::Adjacency, To, VertexIndex};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+};
+
+/// An arc of an [`EdgeList`] with an endpoint outside of its vertices, which a [`CSR`] or an
+/// [`Adjacency`] has no row for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexOutOfRange {
+    pub vertex: usize,
+    pub vertices: usize,
+}
+
+impl Display for VertexOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "vertex {} is out of range for a graph of {} vertices",
+            self.vertex, self.vertices
+        )
+    }
+}
+
+impl std::error::Error for VertexOutOfRange {}
+
+impl<E, Ix> From<Adjacency<E, Ix>> for CSR<E, Ix> {
+    fn from(g: Adjacency<E, Ix>) -> Self {
+        let rows = g.into_rows();
+        let mut row_indexes = Vec::with_capacity(rows.len() + 1);
+        row_indexes.push(0);
+        for row in &rows {
+            row_indexes.push(row_indexes[row_indexes.len() - 1] + row.len());
+        }
+        let columns = rows.into_iter().flatten().collect();
+        Self::from_raw_parts(row_indexes.into_boxed_slice(), columns)
+    }
+}
+
+impl<E, Ix> From<CSR<E, Ix>> for Adjacency<E, Ix> {
+    fn from(g: CSR<E, Ix>) -> Self {
+        let (row_indexes, columns) = g.into_raw_parts();
+        let mut columns = columns.into_iter();
+        let rows = row_indexes
+            .windows(2)
+            .map(|w| columns.by_ref().take(w[1] - w[0]).collect())
+            .collect();
+        Self::from_rows(rows)
+    }
+}
+
+/// The first endpoint of an arc that isn't one of the `vertices` of an edge list.
+fn out_of_range<E, Ix: VertexIndex>(
+    vertices: usize,
+    edges: &[(Ix, Ix, E)],
+) -> Result<(), VertexOutOfRange> {
+    let outside = |(from, to, _): &(Ix, Ix, E)| {
+        [from, to]
+            .iter()
+            .map(|v| v.index())
+            .find(|&v| v >= vertices)
+    };
+    match edges.iter().find_map(outside) {
+        Some(vertex) => Err(VertexOutOfRange { vertex, vertices }),
+        None => Ok(()),
+    }
+}
+
+/// Fails if an arc leaves or reaches a vertex the edge list doesn't have.
+impl<E, Ix: VertexIndex> TryFrom<EdgeList<E, Ix>> for CSR<E, Ix> {
+    type Error = VertexOutOfRange;
+
+    fn try_from(g: EdgeList<E, Ix>) -> Result<Self, Self::Error> {
+        let (vertices, edges) = g.into_raw_parts();
+        out_of_range(vertices, &edges)?;
+        Ok(Self::from_arcs(vertices, edges))
+    }
+}
+
+/// Fails if an arc leaves or reaches a vertex the edge list doesn't have.
+impl<E, Ix: VertexIndex> TryFrom<EdgeList<E, Ix>> for Adjacency<E, Ix> {
+    type Error = VertexOutOfRange;
+
+    fn try_from(g: EdgeList<E, Ix>) -> Result<Self, Self::Error> {
+        let (vertices, edges) = g.into_raw_parts();
+        out_of_range(vertices, &edges)?;
+        let mut rows = Vec::new();
+        rows.resize_with(vertices, Vec::new);
+        for (from, to, weight) in edges {
+            rows[from.index()].push(To { to, weight });
+        }
+        Ok(Self::from_rows(rows))
+    }
+}
+
+impl<E, Ix: VertexIndex> From<CSR<E, Ix>> for EdgeList<E, Ix> {
+    fn from(g: CSR<E, Ix>) -> Self {
+        let (row_indexes, columns) = g.into_raw_parts();
+        let mut v = 0;
+        let edges = columns
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| {
+                while row_indexes[v + 1] <= i {
+                    v += 1;
+                }
+                (Ix::new(v), t.to, t.weight)
+            })
+            .collect();
+        Self::from_raw_parts(row_indexes.len() - 1, edges)
+    }
+}
+
+impl<E, Ix: VertexIndex> From<Adjacency<E, Ix>> for EdgeList<E, Ix> {
+    fn from(g: Adjacency<E, Ix>) -> Self {
+        let rows = g.into_rows();
+        let vertices = rows.len();
+        let edges = rows
+            .into_iter()
+            .enumerate()
+            .flat_map(|(v, row)| row.into_iter().map(move |t| (Ix::new(v), t.to, t.weight)))
+            .collect();
+        Self::from_raw_parts(vertices, edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{test_graphs, EdgeListGraph, Graph, RandomAccess, VertexIndex, WFromEdges};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn arcs() -> Vec<(usize, usize, u32)> {
+        test_graphs::random_graph(100, 1000, StdRng::seed_from_u64(0xBAD5EED))
+            .into_iter()
+            .chain(vec![(3, 7), (3, 7), (5, 5)])
+            .enumerate()
+            .map(|(i, (f, t))| (f, t, i as u32))
+            .collect()
+    }
+
+    fn rows<G: RandomAccess<EdgeWeight = u32>>(g: &G) -> Vec<Vec<(usize, u32)>> {
+        (0..g.vertices())
+            .map(|v| {
+                g.neighbours(v)
+                    .iter()
+                    .map(|w| (w.to.index(), w.weight))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        let csr: CSR<u32> = WFromEdges::from_edges(100, arcs());
+        let expected = rows(&csr);
+        let adjacency = Adjacency::from(csr);
+        assert_eq!(adjacency.edges(), 1003);
+        assert_eq!(rows(&adjacency), expected);
+        let list = EdgeList::from(adjacency);
+        assert_eq!(list.vertices(), 100);
+        let csr = CSR::try_from(list).unwrap();
+        assert_eq!(rows(&csr), expected);
+        let list = EdgeList::from(csr);
+        let mut sorted = arcs();
+        sorted.sort_by_key(|&(f, _, i)| (f, i));
+        assert_eq!(list.as_edges(), &sorted[..]);
+        let adjacency = Adjacency::try_from(list).unwrap();
+        let csr = CSR::from(adjacency);
+        assert_eq!(rows(&csr), expected);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let list: EdgeList<u32> = WFromEdges::from_edges(3, vec![(0, 1, 1), (4, 2, 2)]);
+        assert_eq!(
+            CSR::try_from(list).unwrap_err(),
+            VertexOutOfRange {
+                vertex: 4,
+                vertices: 3
+            }
+        );
+        let list: EdgeList<u32> = WFromEdges::from_edges(3, vec![(0, 1, 1), (4, 2, 2)]);
+        assert_eq!(
+            Adjacency::try_from(list).err(),
+            Some(VertexOutOfRange {
+                vertex: 4,
+                vertices: 3
+            })
+        );
+        let arcs = vec![(0, 1, 1), (2, 5, 2)];
+        let list: EdgeList<u32> = WFromEdges::from_edges(3, arcs.clone());
+        assert_eq!(
+            CSR::try_from(list).unwrap_err(),
+            VertexOutOfRange {
+                vertex: 5,
+                vertices: 3
+            }
+        );
+        let list: EdgeList<u32> = WFromEdges::from_edges(3, arcs);
+        assert_eq!(
+            Adjacency::try_from(list).err(),
+            Some(VertexOutOfRange {
+                vertex: 5,
+                vertices: 3
+            })
+        );
+    }
+}
@@ -1,8 +1,13 @@
-use super::{Graph, To, WEdge, WFromEdges};
+use super::{
+    transform::{self, Transform},
+    Graph, To, VertexIndex, WEdge, WFromEdges, WMutable,
+};
 use itertools::Itertools;
+use rayon::prelude::*;
 use std::{
     fmt::{self, Debug},
-    ops::Index,
+    ops::{Index, IndexMut},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 /// CSR (compressed sparse row)
@@ -17,17 +22,22 @@ use std::{
 /// [0,       3,     5,        8]
 /// ```
 ///
-/// Changing is hard
+/// Changing is hard, each [`add_weighed_link`](WMutable::add_weighed_link) shifts the arcs
+/// after it and the boundaries of the rows after `from`, so build big graphs with
+/// [`CsrBuilder`] or [`from_edges`](WFromEdges::from_edges) instead.
 ///
 /// You can do row or column oriented. This is imporant if it's a matrix and not a graph and
 /// multiplication is done.
-#[derive(Default)]
-pub struct CSR<E = ()> {
-    columns: Vec<To<E>>,
+///
+/// The vertex ids of the arcs are stored as `Ix`, see [`VertexIndex`]. The row boundaries are
+/// offsets into the arcs and stay `usize`, as big graphs have more arcs than vertices.
+#[derive(Clone)]
+pub struct CSR<E = (), Ix = usize> {
+    columns: Vec<To<E, Ix>>,
     row_indexes: Box<[usize]>,
 }
 
-impl<E> Graph for CSR<E> {
+impl<E, Ix> Graph for CSR<E, Ix> {
     type EdgeWeight = E;
     fn vertices(&self) -> usize {
         self.row_indexes.len() - 1
@@ -38,50 +48,231 @@ impl<E> Graph for CSR<E> {
     }
 }
 
-impl<E> WFromEdges for CSR<E> {
-    /// Create a new CSR
+impl<E, Ix> Default for CSR<E, Ix> {
+    fn default() -> Self {
+        Self::from_raw_parts(vec![0].into_boxed_slice(), Vec::new())
+    }
+}
+
+impl<E, Ix: VertexIndex> WMutable for CSR<E, Ix> {
+    /// Adds the arc at the end of the neighbours of `from`, adding vertices up to `from` and
+    /// `to` if they don't exist yet.
+    fn add_weighed_link(&mut self, from: usize, to: usize, weight: E) -> bool {
+        self.add_vertex(usize::max(from, to));
+        let end = self.row_indexes[from + 1];
+        self.columns.insert(
+            end,
+            To {
+                to: Ix::new(to),
+                weight,
+            },
+        );
+        self.row_indexes[(from + 1)..]
+            .iter_mut()
+            .for_each(|i| *i += 1);
+        true
+    }
+
+    fn add_vertex(&mut self, v: usize) {
+        if v >= self.vertices() {
+            let mut row_indexes = std::mem::take(&mut self.row_indexes).into_vec();
+            row_indexes.resize(v + 2, self.columns.len());
+            self.row_indexes = row_indexes.into_boxed_slice();
+        }
+    }
+}
+
+impl<E, Ix: VertexIndex> WFromEdges for CSR<E, Ix> {
+    /// Create a new CSR with a counting sort over the sources of the arcs, in `O(n + m)` time.
+    ///
+    /// The neighbours of each vertex are in the order their arcs were given, use
+    /// [`sort_neighbours`](CSR::sort_neighbours) to order them by id.
     fn from_edges<I, Iter>(n: usize, list: I) -> Self
     where
         I: IntoIterator<IntoIter = Iter, Item = WEdge<E>>,
         Iter: ExactSizeIterator<Item = WEdge<E>>,
     {
-        let mut edges = list.into_iter();
-        let mut s = Self {
-            columns: Vec::with_capacity(edges.len()),
-            row_indexes: vec![0; n + 1].into_boxed_slice(),
-        };
-        assert!(edges.all(|(from, to, e)| { s.add_weighed_link(from, to, e) }));
-        s
+        let edges = list
+            .into_iter()
+            .map(|(from, to, weight)| (Ix::new(from), Ix::new(to), weight))
+            .collect();
+        Self::from_arcs(n, edges)
+    }
+}
+
+impl<E, Ix: VertexIndex> CSR<E, Ix> {
+    /// [`from_edges`](WFromEdges::from_edges), with the arcs already in vertex ids of type `Ix`.
+    pub(crate) fn from_arcs(n: usize, mut edges: Vec<(Ix, Ix, E)>) -> Self {
+        let mut row_indexes = vec![0; n + 1].into_boxed_slice();
+        for &(from, to, _) in &edges {
+            let (from, to) = (from.index(), to.index());
+            assert!(from < n, "arc from {} in a graph of {} vertices", from, n);
+            assert!(to < n, "arc to {} in a graph of {} vertices", to, n);
+            row_indexes[from + 1] += 1;
+        }
+        for i in 0..n {
+            row_indexes[i + 1] += row_indexes[i];
+        }
+        let mut next = row_indexes[..n].to_vec();
+        let mut positions = edges
+            .iter()
+            .map(|&(from, _, _)| {
+                next[from.index()] += 1;
+                next[from.index()] - 1
+            })
+            .collect::<Vec<_>>();
+        // Apply the permutation in place, following its cycles
+        for i in 0..edges.len() {
+            while positions[i] != i {
+                let j = positions[i];
+                edges.swap(i, j);
+                positions.swap(i, j);
+            }
+        }
+        let columns = edges
+            .into_iter()
+            .map(|(_, to, weight)| To { to, weight })
+            .collect();
+        Self::from_raw_parts(row_indexes, columns)
+    }
+}
+
+impl<E: Send, Ix: VertexIndex> CSR<E, Ix> {
+    /// Like [`sort_neighbours`](CSR::sort_neighbours), sorting the rows in parallel.
+    pub fn par_sort_neighbours(&mut self) {
+        let mut rows = Vec::with_capacity(self.vertices());
+        let mut rest = &mut self.columns[..];
+        for w in self.row_indexes.windows(2) {
+            let (row, tail) = rest.split_at_mut(w[1] - w[0]);
+            rows.push(row);
+            rest = tail;
+        }
+        rows.into_par_iter()
+            .for_each(|row| row.sort_by_key(|t| t.to));
+    }
+}
+
+impl<E: Clone + Send + Sync, Ix: VertexIndex> CSR<E, Ix> {
+    /// Builds the same CSR as [`from_edges`](WFromEdges::from_edges), counting and scattering
+    /// the arcs in parallel.
+    ///
+    /// The arcs are split in chunks, each counting the out-degrees of its arcs, so every chunk
+    /// knows where its arcs start in each row and can place them independently. Each chunk keeps
+    /// a count per vertex, so there are no more chunks than threads nor than `m / n`, which
+    /// keeps the counts in `O(n + m)` memory.
+    pub fn par_from_edges<I, Iter>(n: usize, list: I) -> Self
+    where
+        I: IntoIterator<IntoIter = Iter, Item = WEdge<E>>,
+        Iter: ExactSizeIterator<Item = WEdge<E>>,
+    {
+        let edges = list
+            .into_iter()
+            .map(|(from, to, weight)| (Ix::new(from), Ix::new(to), weight))
+            .collect();
+        Self::par_from_arcs(n, edges)
+    }
+
+    /// [`par_from_edges`](CSR::par_from_edges), with the arcs already in vertex ids of type
+    /// `Ix`.
+    pub(crate) fn par_from_arcs(n: usize, edges: Vec<(Ix, Ix, E)>) -> Self {
+        let chunks = usize::min(rayon::current_num_threads(), edges.len() / n.max(1));
+        let chunk_len = edges.len().div_ceil(chunks.max(1)).max(1);
+        let mut counts = edges
+            .par_chunks(chunk_len)
+            .map(|chunk| {
+                let mut degrees = vec![0; n];
+                for &(from, to, _) in chunk {
+                    let (from, to) = (from.index(), to.index());
+                    assert!(from < n, "arc from {} in a graph of {} vertices", from, n);
+                    assert!(to < n, "arc to {} in a graph of {} vertices", to, n);
+                    degrees[from] += 1;
+                }
+                degrees
+            })
+            .collect::<Vec<_>>();
+        let degrees = (0..n)
+            .into_par_iter()
+            .map(|v| counts.iter().map(|c| c[v]).sum::<usize>())
+            .collect::<Vec<_>>();
+        let mut row_indexes = vec![0; n + 1].into_boxed_slice();
+        for (i, d) in degrees.into_iter().enumerate() {
+            row_indexes[i + 1] = row_indexes[i] + d;
+        }
+        // Turn the counts of each chunk into the positions where its arcs start
+        let mut next = row_indexes[..n].to_vec();
+        for chunk in &mut counts {
+            chunk
+                .par_iter_mut()
+                .zip(next.par_iter_mut())
+                .for_each(|(count, next)| {
+                    let c = *count;
+                    *count = *next;
+                    *next += c;
+                });
+        }
+        // The chunks write to disjoint positions, the atomics only let them share the slice
+        let sources = (0..edges.len())
+            .into_par_iter()
+            .map(|_| AtomicUsize::new(0))
+            .collect::<Vec<_>>();
+        edges
+            .par_chunks(chunk_len)
+            .enumerate()
+            .zip(counts)
+            .for_each(|((c, chunk), mut next)| {
+                for (i, &(from, _, _)) in chunk.iter().enumerate() {
+                    sources[next[from.index()]].store(c * chunk_len + i, Ordering::Relaxed);
+                    next[from.index()] += 1;
+                }
+            });
+        let columns = sources
+            .into_par_iter()
+            .map(|i| {
+                let (_, to, weight) = &edges[i.into_inner()];
+                To {
+                    to: *to,
+                    weight: weight.clone(),
+                }
+            })
+            .collect();
+        Self::from_raw_parts(row_indexes, columns)
     }
 }
 
-impl<E> CSR<E> {
-    pub fn neighbours(&self, i: usize) -> impl Iterator<Item = &To<E>> {
+impl<E, Ix> CSR<E, Ix> {
+    /// Builds a CSR out of already laid out rows, `columns[row_indexes[i]..row_indexes[i + 1]]`
+    /// being the neighbours of `i`.
+    pub(crate) fn from_raw_parts(row_indexes: Box<[usize]>, columns: Vec<To<E, Ix>>) -> Self {
+        debug_assert_eq!(row_indexes.last().copied(), Some(columns.len()));
+        Self {
+            columns,
+            row_indexes,
+        }
+    }
+
+    pub(crate) fn into_raw_parts(self) -> (Box<[usize]>, Vec<To<E, Ix>>) {
+        (self.row_indexes, self.columns)
+    }
+
+    pub fn neighbours(&self, i: usize) -> impl Iterator<Item = &To<E, Ix>> {
         let from = self.row_indexes[i];
         let to = self.row_indexes[i + 1];
         self.columns[from..to].iter()
     }
 
-    /// Add a link to the CSR
-    fn add_weighed_link(&mut self, from: usize, to: usize, weight: E) -> bool {
-        if from > self.row_indexes.len() {
-            return false;
-        }
-        let start_of_neighbours = self.row_indexes[from];
-        if !self
-            .columns
-            .insert_checked(start_of_neighbours, To { to, weight })
-        {
-            return false;
+    /// Sorts the neighbours of every vertex by id, keeping the order of the arcs to the same
+    /// one.
+    pub fn sort_neighbours(&mut self)
+    where
+        Ix: Ord + Copy,
+    {
+        for w in self.row_indexes.windows(2) {
+            self.columns[w[0]..w[1]].sort_by_key(|t| t.to);
         }
-        self.row_indexes[(from + 1)..]
-            .iter_mut()
-            .for_each(|i| *i += 1);
-        true
     }
 
     /// Iterate over the neighbours of each edge.
-    pub fn neighbourhoods(&self) -> impl Iterator<Item = &[To<E>]> {
+    pub fn neighbourhoods(&self) -> impl Iterator<Item = &[To<E, Ix>]> {
         self.row_indexes
             .iter()
             .tuple_windows()
@@ -93,9 +284,74 @@ impl<E> CSR<E> {
     }
 }
 
+impl<E, Ix: VertexIndex> CSR<E, Ix> {
+    /// Indexes the predecessors of every vertex, with the counting sort of
+    /// [`transpose`](Transform::transpose) but without copying the weights.
+    pub fn reverse_adjacency(&self) -> ReverseAdjacency<'_, E, Ix> {
+        let (row_indexes, sources) = transform::transposed_with(self, |v, _| Ix::new(v));
+        ReverseAdjacency {
+            graph: self,
+            row_indexes,
+            sources: sources.into_boxed_slice(),
+        }
+    }
+}
+
+impl<E, Ix: VertexIndex> Transform for CSR<E, Ix> {
+    fn transpose(&self) -> Self
+    where
+        E: Clone,
+    {
+        let (row_indexes, columns) = transform::transposed(self);
+        Self::from_raw_parts(row_indexes, columns)
+    }
+
+    fn symmetrize<F>(&self, merge: F) -> Self
+    where
+        E: Clone,
+        F: FnMut(E, E) -> E,
+    {
+        let (row_indexes, columns) = transform::symmetrized(self, merge);
+        Self::from_raw_parts(row_indexes, columns)
+    }
+
+    /// Compacts the columns in place, each row end being read before it is overwritten.
+    fn remove_self_loops(&mut self) {
+        let n = self.vertices();
+        let row_indexes = &mut self.row_indexes;
+        let (mut v, mut i, mut kept) = (0, 0, 0);
+        self.columns.retain(|w| {
+            while v < n && row_indexes[v + 1] == i {
+                row_indexes[v + 1] = kept;
+                v += 1;
+            }
+            i += 1;
+            let keep = w.to.index() != v;
+            kept += keep as usize;
+            keep
+        });
+        row_indexes[v + 1..].iter_mut().for_each(|r| *r = kept);
+    }
+
+    fn dedup_edges<F>(&mut self, mut merge: F)
+    where
+        F: FnMut(E, E) -> E,
+    {
+        let mut old = std::mem::take(&mut self.columns).into_iter();
+        let mut row = Vec::new();
+        let mut start = 0;
+        for end in self.row_indexes[1..].iter_mut() {
+            row.extend(old.by_ref().take(*end - start).map(|w| ((), w)));
+            start = *end;
+            transform::push_deduped(&mut self.columns, &mut row, &mut merge);
+            *end = self.columns.len();
+        }
+    }
+}
+
 /// Indexing a graph with a node returns a view of the neighbours of that node.
-impl<E> Index<usize> for CSR<E> {
-    type Output = [To<E>];
+impl<E, Ix> Index<usize> for CSR<E, Ix> {
+    type Output = [To<E, Ix>];
     fn index(&self, i: usize) -> &Self::Output {
         let from = self.row_indexes[i];
         let to = self.row_indexes[i + 1];
@@ -103,9 +359,20 @@ impl<E> Index<usize> for CSR<E> {
     }
 }
 
-impl<E> Debug for CSR<E>
+impl<E, Ix> IndexMut<usize> for CSR<E, Ix> {
+    fn index_mut(&mut self, i: usize) -> &mut Self::Output {
+        let from = self.row_indexes[i];
+        let to = self.row_indexes[i + 1];
+        &mut self.columns[from..to]
+    }
+}
+
+super::impl_indexed_graph!(CSR);
+
+impl<E, Ix> Debug for CSR<E, Ix>
 where
     E: Debug,
+    Ix: Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.neighbourhoods()
@@ -114,21 +381,228 @@ where
     }
 }
 
-// *X >= 0 -> single
-// *X < 0 -> pair(abs(*X), X + 1)
+/// Collects arcs one at a time, without knowing how many there will be, and lays them out as a
+/// CSR once they are all in.
+#[derive(Clone)]
+pub struct CsrBuilder<E = (), Ix = usize> {
+    edges: Vec<(Ix, Ix, E)>,
+    n_vertices: usize,
+}
+
+impl<E, Ix> Default for CsrBuilder<E, Ix> {
+    fn default() -> Self {
+        Self::with_capacity(0, 0)
+    }
+}
+
+impl<E> CsrBuilder<E> {
+    /// A builder of a CSR with `usize` vertex ids, use [`Default`] for the others.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<E, Ix> CsrBuilder<E, Ix> {
+    pub fn with_capacity(vertices: usize, edges: usize) -> Self {
+        Self {
+            edges: Vec::with_capacity(edges),
+            n_vertices: vertices,
+        }
+    }
+
+    /// The CSR with every arc added, the neighbours of each vertex in the order their arcs
+    /// were.
+    pub fn build(self) -> CSR<E, Ix>
+    where
+        Ix: VertexIndex,
+    {
+        CSR::from_arcs(self.n_vertices, self.edges)
+    }
+}
+
+impl<E: Clone + Send + Sync, Ix: VertexIndex> CsrBuilder<E, Ix> {
+    /// Like [`build`](CsrBuilder::build), laying out the arcs in parallel.
+    pub fn par_build(self) -> CSR<E, Ix> {
+        CSR::par_from_arcs(self.n_vertices, self.edges)
+    }
+}
+
+impl<E, Ix> Graph for CsrBuilder<E, Ix> {
+    type EdgeWeight = E;
+    fn vertices(&self) -> usize {
+        self.n_vertices
+    }
 
-trait StaticVec<T> {
-    fn insert_checked(&mut self, index: usize, val: T) -> bool;
+    fn edges(&self) -> usize {
+        self.edges.len()
+    }
 }
 
-impl<T> StaticVec<T> for Vec<T> {
-    #[inline(always)]
-    fn insert_checked(&mut self, index: usize, val: T) -> bool {
-        if self.len() != self.capacity() {
-            self.insert(index, val);
-            true
-        } else {
-            false
+impl<E, Ix: VertexIndex> WMutable for CsrBuilder<E, Ix> {
+    /// Adds the arc, and the vertices up to `from` and `to` if they don't exist yet.
+    fn add_weighed_link(&mut self, from: usize, to: usize, weight: E) -> bool {
+        self.add_vertex(usize::max(from, to));
+        self.edges.push((Ix::new(from), Ix::new(to), weight));
+        true
+    }
+
+    fn add_vertex(&mut self, v: usize) {
+        self.n_vertices = usize::max(self.n_vertices, v + 1);
+    }
+}
+
+impl<E, Ix: VertexIndex> Extend<WEdge<E>> for CsrBuilder<E, Ix> {
+    fn extend<I: IntoIterator<Item = WEdge<E>>>(&mut self, edges: I) {
+        edges.into_iter().for_each(|(from, to, weight)| {
+            self.add_weighed_link(from, to, weight);
+        });
+    }
+}
+
+/// A CSR together with the predecessors of each of its vertices.
+pub struct ReverseAdjacency<'g, E, Ix = usize> {
+    graph: &'g CSR<E, Ix>,
+    row_indexes: Box<[usize]>,
+    sources: Box<[Ix]>,
+}
+
+impl<'g, E, Ix> ReverseAdjacency<'g, E, Ix> {
+    pub fn graph(&self) -> &'g CSR<E, Ix> {
+        self.graph
+    }
+
+    /// The vertices with an arc to `i`, in increasing order, repeated once per arc.
+    pub fn predecessors(&self, i: usize) -> &[Ix] {
+        &self.sources[self.row_indexes[i]..self.row_indexes[i + 1]]
+    }
+
+    pub fn successors(&self, i: usize) -> &'g [To<E, Ix>] {
+        &self.graph[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::apl::hyper_ball::hyper_ball,
+        graphs::{test_graphs::random_graph, FromEdges, Mutable},
+        util::hyper_counters::{HyperLogLog, B},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    #[should_panic(expected = "arc to 3 in a graph of 3 vertices")]
+    fn target_out_of_range() {
+        <CSR as FromEdges>::from_edges(3, vec![(0, 1), (1, 3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "arc to 3 in a graph of 3 vertices")]
+    fn par_target_out_of_range() {
+        CSR::<()>::par_from_edges(3, vec![(0, 1, ()), (1, 3, ())]);
+    }
+
+    #[test]
+    fn keeps_the_order_of_the_arcs() {
+        let g: CSR<u32> =
+            WFromEdges::from_edges(3, vec![(1, 2, 0), (0, 1, 1), (1, 0, 2), (1, 2, 3)]);
+        assert_eq!(&g.row_indexes[..], &[0, 1, 4, 4]);
+        let row = g[1].iter().map(|w| (w.to, w.weight)).collect::<Vec<_>>();
+        assert_eq!(row, vec![(2, 0), (0, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn grows() {
+        let mut g = CSR::<u32>::default();
+        assert_eq!(g.vertices(), 0);
+        assert!(g.add_weighed_link(2, 0, 1));
+        assert!(g.add_weighed_link(0, 1, 2));
+        assert!(g.add_weighed_link(2, 4, 3));
+        g.add_vertex(5);
+        assert_eq!(g.vertices(), 6);
+        assert_eq!(g.edges(), 3);
+        assert_eq!(&g.row_indexes[..], &[0, 1, 1, 3, 3, 3, 3]);
+        let row = g[2].iter().map(|w| (w.to, w.weight)).collect::<Vec<_>>();
+        assert_eq!(row, vec![(0, 1), (4, 3)]);
+
+        let edges = random_graph(50, 400, StdRng::seed_from_u64(0xBAD5EED));
+        let mut builder = CsrBuilder::new();
+        builder.extend(edges.iter().map(|&(f, t)| (f, t, ())));
+        builder.add_vertex(59);
+        let mut mutated = CSR::default();
+        edges.iter().for_each(|&(f, t)| {
+            mutated.add_link(f, t);
+        });
+        mutated.add_vertex(59);
+        let built = builder.build();
+        assert_eq!(built.vertices(), 60);
+        assert_eq!(built.row_indexes, mutated.row_indexes);
+        assert_eq!(built.columns, mutated.columns);
+    }
+
+    #[test]
+    fn parallel_and_sorted() {
+        let edges = random_graph(300, 5000, StdRng::seed_from_u64(0xBAD5EED))
+            .into_iter()
+            .enumerate()
+            .map(|(i, (f, t))| (f, t, i))
+            .collect::<Vec<_>>();
+        let mut g: CSR<usize> = WFromEdges::from_edges(300, edges.clone());
+        let mut p: CSR<usize> = CSR::par_from_edges(300, edges.clone());
+        assert_eq!(g.row_indexes, p.row_indexes);
+        assert_eq!(g.columns, p.columns);
+        // more vertices than arcs, so a single chunk
+        let sparse: CSR<usize, u32> = CSR::par_from_edges(10_000, edges.iter().cloned());
+        assert_eq!(&sparse.row_indexes[..=300], &g.row_indexes[..]);
+        assert!(sparse.row_indexes[300..].iter().all(|&r| r == edges.len()));
+        assert!(sparse
+            .columns
+            .iter()
+            .map(|w| w.to as usize)
+            .eq(g.columns.iter().map(|w| w.to)));
+        g.sort_neighbours();
+        p.par_sort_neighbours();
+        assert_eq!(g.columns, p.columns);
+        for row in g.neighbourhoods() {
+            assert!(row
+                .windows(2)
+                .all(|w| (w[0].to, w[0].weight) < (w[1].to, w[1].weight)));
+        }
+    }
+
+    #[test]
+    fn narrow_indexes() {
+        let edges = random_graph(300, 5000, StdRng::seed_from_u64(0xBAD5EED));
+        let g = <CSR as FromEdges>::from_edges(300, edges.clone());
+        let narrow = <CSR<(), u32> as FromEdges>::from_edges(300, edges.clone());
+        assert_eq!(g.row_indexes, narrow.row_indexes);
+        assert!(g
+            .columns
+            .iter()
+            .map(|w| w.to)
+            .eq(narrow.columns.iter().map(|w| w.to as usize)));
+        let mut builder = CsrBuilder::<(), u32>::default();
+        builder.extend(edges.iter().map(|&(f, t)| (f, t, ())));
+        let built = builder.par_build();
+        assert_eq!(built.row_indexes, narrow.row_indexes);
+        assert_eq!(built.columns, narrow.columns);
+        let counters =
+            || vec![HyperLogLog::new_with_seed(B::B4, 0xBAD5EED); 300].into_boxed_slice();
+        assert_eq!(hyper_ball(&narrow, counters()), hyper_ball(&g, counters()));
+    }
+
+    #[test]
+    fn reverse_adjacency_matches_transpose() {
+        let edges = random_graph(100, 1000, StdRng::seed_from_u64(0xBAD5EED));
+        let g = <CSR<(), u32> as FromEdges>::from_edges(100, edges);
+        let reverse = g.reverse_adjacency();
+        let transposed = g.transpose();
+        for v in g.nodes() {
+            assert!(reverse
+                .predecessors(v)
+                .iter()
+                .eq(transposed[v].iter().map(|w| &w.to)));
         }
     }
 }
@@ -1,11 +1,16 @@
-use crate::graphs::{EdgeListGraph, WFromEdges, Graph, WEdge};
+use crate::graphs::{
+    transform::Transform, EdgeListGraph, Graph, Successors, To, VertexIndex, WEdge, WFromEdges,
+    WSuccessors,
+};
+use std::{iter::Map, slice};
 
-pub struct EdgeList<E = ()> {
-    edges: Vec<WEdge<E>>,
+/// The endpoints of the arcs are stored as `Ix`, see [`VertexIndex`].
+pub struct EdgeList<E = (), Ix = usize> {
+    edges: Vec<(Ix, Ix, E)>,
     n_vertices: usize,
 }
 
-impl<E> Graph for EdgeList<E> {
+impl<E, Ix> Graph for EdgeList<E, Ix> {
     type EdgeWeight = E;
     fn vertices(&self) -> usize {
         self.n_vertices
@@ -16,7 +21,7 @@ impl<E> Graph for EdgeList<E> {
     }
 }
 
-impl<E> WFromEdges for EdgeList<E> {
+impl<E, Ix: VertexIndex> WFromEdges for EdgeList<E, Ix> {
     fn from_edges<I, Iter>(n: usize, list: I) -> Self
     where
         I: IntoIterator<IntoIter = Iter, Item = WEdge<E>>,
@@ -24,19 +29,23 @@ impl<E> WFromEdges for EdgeList<E> {
     {
         Self {
             n_vertices: n,
-            edges: list.into_iter().collect(),
+            edges: list
+                .into_iter()
+                .map(|(from, to, w)| (Ix::new(from), Ix::new(to), w))
+                .collect(),
         }
     }
 }
 
-impl<E> EdgeListGraph for EdgeList<E> {
-    type Edges = Vec<WEdge<Self::EdgeWeight>>;
+impl<E, Ix: VertexIndex> EdgeListGraph for EdgeList<E, Ix> {
+    type Index = Ix;
+    type Edges = Vec<(Ix, Ix, E)>;
 
-    fn as_edges(&self) -> &[WEdge<E>] {
+    fn as_edges(&self) -> &[(Ix, Ix, E)] {
         &self.edges[..]
     }
 
-    fn as_edges_mut(&mut self) -> &mut [WEdge<E>] {
+    fn as_edges_mut(&mut self) -> &mut [(Ix, Ix, E)] {
         &mut self.edges[..]
     }
 
@@ -56,13 +65,154 @@ impl EdgeList<()> {
     }
 }
 
-impl<E> EdgeList<E> {
+impl<E, Ix> EdgeList<E, Ix> {
+    pub(crate) fn from_raw_parts(n_vertices: usize, edges: Vec<(Ix, Ix, E)>) -> Self {
+        Self { edges, n_vertices }
+    }
+
+    pub(crate) fn into_raw_parts(self) -> (usize, Vec<(Ix, Ix, E)>) {
+        (self.n_vertices, self.edges)
+    }
+}
+
+impl<E, Ix: VertexIndex> EdgeList<E, Ix> {
+    /// The arcs, with their endpoints as they are stored.
+    pub fn arcs(&self) -> &[(Ix, Ix, E)] {
+        &self.edges
+    }
+
+    /// The arcs leaving `from`, found by going through every arc of the graph.
+    pub fn out_arcs(&self, from: usize) -> OutArcs<'_, E, Ix> {
+        OutArcs {
+            arcs: self.edges.iter(),
+            from,
+        }
+    }
+
     pub fn add_link_weights(&mut self, from: usize, to: usize, e: E) -> bool {
         if self.edges.capacity() == self.edges.len() {
             false
         } else {
-            self.edges.push((from, to, e));
+            self.edges.push((Ix::new(from), Ix::new(to), e));
             true
         }
     }
 }
+
+/// An edge list isn't indexed by vertex, so listing the successors of every vertex takes
+/// `O(nm)` time. Algorithms traversing the graph should run on a [`CSR`](super::csr::CSR)
+/// built from it instead.
+impl<E, Ix: VertexIndex> Successors for EdgeList<E, Ix> {
+    type Successors<'a>
+        = Map<OutArcs<'a, E, Ix>, fn(&(Ix, Ix, E)) -> usize>
+    where
+        Self: 'a;
+
+    fn successors(&self, from: usize) -> Self::Successors<'_> {
+        self.out_arcs(from).map(|(_, to, _)| to.index())
+    }
+}
+
+impl<E: Clone, Ix: VertexIndex> WSuccessors for EdgeList<E, Ix> {
+    type WSuccessors<'a>
+        = Map<OutArcs<'a, E, Ix>, fn(&(Ix, Ix, E)) -> To<E>>
+    where
+        Self: 'a;
+
+    fn weighted_successors(&self, from: usize) -> Self::WSuccessors<'_> {
+        self.out_arcs(from).map(|(_, to, w)| To {
+            to: to.index(),
+            weight: w.clone(),
+        })
+    }
+}
+
+/// The arcs of an [`EdgeList`] leaving a vertex, see [`EdgeList::out_arcs`].
+pub struct OutArcs<'g, E, Ix> {
+    arcs: slice::Iter<'g, (Ix, Ix, E)>,
+    from: usize,
+}
+
+impl<'g, E, Ix: VertexIndex> Iterator for OutArcs<'g, E, Ix> {
+    type Item = &'g (Ix, Ix, E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let from = self.from;
+        self.arcs.find(|(f, _, _)| f.index() == from)
+    }
+}
+
+/// Sorts the arcs by their endpoints and then by their keys, keeping the order they had among
+/// equal ones, and merges the weights of the arcs between the same endpoints in that order.
+fn merged<Ix, K, E, F>(mut arcs: Vec<(Ix, Ix, K, E)>, mut merge: F) -> Vec<(Ix, Ix, E)>
+where
+    Ix: VertexIndex,
+    K: Ord,
+    F: FnMut(E, E) -> E,
+{
+    arcs.sort_by(|(f0, t0, k0, _), (f1, t1, k1, _)| (f0, t0, k0).cmp(&(f1, t1, k1)));
+    let mut edges: Vec<(Ix, Ix, E)> = Vec::with_capacity(arcs.len());
+    for (from, to, _, w) in arcs {
+        match edges.last() {
+            Some(&(f, t, _)) if (f, t) == (from, to) => {
+                let (_, _, last) = edges.pop().unwrap();
+                edges.push((from, to, merge(last, w)));
+            }
+            _ => edges.push((from, to, w)),
+        }
+    }
+    edges
+}
+
+impl<E, Ix: VertexIndex> Transform for EdgeList<E, Ix> {
+    fn transpose(&self) -> Self
+    where
+        E: Clone,
+    {
+        Self {
+            n_vertices: self.n_vertices,
+            edges: self
+                .edges
+                .iter()
+                .map(|(from, to, w)| (*to, *from, w.clone()))
+                .collect(),
+        }
+    }
+
+    /// The result is sorted by source and then by target.
+    fn symmetrize<F>(&self, merge: F) -> Self
+    where
+        E: Clone,
+        F: FnMut(E, E) -> E,
+    {
+        let mut arcs = Vec::with_capacity(2 * self.edges.len());
+        for (from, to, w) in &self.edges {
+            // Keyed so the weights of the arcs leaving the smaller vertex are merged first
+            let key = from > to;
+            arcs.push((*from, *to, key, w.clone()));
+            if from != to {
+                arcs.push((*to, *from, key, w.clone()));
+            }
+        }
+        Self {
+            n_vertices: self.n_vertices,
+            edges: merged(arcs, merge),
+        }
+    }
+
+    fn remove_self_loops(&mut self) {
+        self.edges.retain(|(from, to, _)| from != to);
+    }
+
+    /// The result is sorted by source and then by target.
+    fn dedup_edges<F>(&mut self, merge: F)
+    where
+        F: FnMut(E, E) -> E,
+    {
+        let arcs = std::mem::take(&mut self.edges)
+            .into_iter()
+            .map(|(from, to, w)| (from, to, (), w))
+            .collect();
+        self.edges = merged(arcs, merge);
+    }
+}
@@ -0,0 +1,507 @@
+//! Reading and writing graphs as text.
+//!
+//! Two formats are supported:
+//! - Edge lists, one `from to [weight]` edge per line, separated by any whitespace. Lines starting
+//!   with `#` or `%` are comments, as in the [SNAP](https://snap.stanford.edu/data/) dumps.
+//! - [Matrix Market](https://math.nist.gov/MatrixMarket/formats.html) coordinate files, where
+//!   vertices are 1-indexed and `symmetric` matrices store each edge once.
+use super::{
+    csr::CSR, edge_list::EdgeList, matrix::Adjacency, Graph, VertexIndex, WEdge, WFromEdges,
+};
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// A line that doesn't have the expected shape.
+    Malformed { line: usize, content: String },
+    /// A line whose weight column is missing or can't be parsed.
+    InvalidWeight { line: usize, content: String },
+    /// A vertex id that doesn't fit in the declared number of vertices.
+    VertexOutOfRange {
+        line: usize,
+        vertex: usize,
+        vertices: usize,
+    },
+    /// A Matrix Market header that is missing or not supported.
+    InvalidHeader(String),
+    /// A Matrix Market file with a number of entries different from the declared one.
+    EntryCountMismatch { expected: usize, found: usize },
+    /// A Matrix Market matrix with a different number of rows and columns.
+    NotSquare { rows: usize, cols: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Malformed { line, content } => {
+                write!(f, "line {}: malformed edge '{}'", line, content)
+            }
+            Error::InvalidWeight { line, content } => {
+                write!(f, "line {}: invalid weight in '{}'", line, content)
+            }
+            Error::VertexOutOfRange {
+                line,
+                vertex,
+                vertices,
+            } => write!(
+                f,
+                "line {}: vertex {} out of range for a graph with {} vertices",
+                line, vertex, vertices
+            ),
+            Error::InvalidHeader(h) => write!(f, "invalid matrix market header '{}'", h),
+            Error::EntryCountMismatch { expected, found } => {
+                write!(f, "expected {} entries but found {}", expected, found)
+            }
+            Error::NotSquare { rows, cols } => write!(
+                f,
+                "a {}x{} matrix isn't the adjacency matrix of a graph",
+                rows, cols
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Edge weights that can be read from and written to text.
+pub trait Weight: Sized {
+    /// The matrix market field used for matrices with this weight.
+    const FIELD: &'static str;
+
+    /// Parses the weight column of an edge, which is `None` if the line has no such column.
+    fn parse_weight(s: Option<&str>) -> Option<Self>;
+
+    /// Writes the weight column of an edge, including the leading separator.
+    fn write_weight<W: Write>(&self, w: &mut W) -> io::Result<()>;
+}
+
+impl Weight for () {
+    const FIELD: &'static str = "pattern";
+
+    /// Unweighted graphs ignore any extra column.
+    fn parse_weight(_: Option<&str>) -> Option<Self> {
+        Some(())
+    }
+
+    fn write_weight<W: Write>(&self, _: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_weight {
+    ($field:expr => $($t:ty),*) => {
+        $(
+        impl Weight for $t {
+            const FIELD: &'static str = $field;
+
+            fn parse_weight(s: Option<&str>) -> Option<Self> {
+                s?.parse().ok()
+            }
+
+            fn write_weight<W: Write>(&self, w: &mut W) -> io::Result<()> {
+                write!(w, " {}", self)
+            }
+        }
+        )*
+    };
+}
+impl_weight!("integer" => i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_weight!("real" => f32, f64);
+
+/// Graphs whose edges can be enumerated in order to be written out.
+pub trait EdgeSource: Graph {
+    fn try_for_each_edge<F, Er>(&self, f: F) -> Result<(), Er>
+    where
+        F: FnMut(usize, usize, &Self::EdgeWeight) -> Result<(), Er>;
+}
+
+impl<E, Ix: VertexIndex> EdgeSource for EdgeList<E, Ix> {
+    fn try_for_each_edge<F, Er>(&self, mut f: F) -> Result<(), Er>
+    where
+        F: FnMut(usize, usize, &E) -> Result<(), Er>,
+    {
+        self.arcs()
+            .iter()
+            .try_for_each(|(a, b, w)| f(a.index(), b.index(), w))
+    }
+}
+
+impl<E, Ix: VertexIndex> EdgeSource for CSR<E, Ix> {
+    fn try_for_each_edge<F, Er>(&self, mut f: F) -> Result<(), Er>
+    where
+        F: FnMut(usize, usize, &E) -> Result<(), Er>,
+    {
+        self.neighbourhoods()
+            .enumerate()
+            .try_for_each(|(v, ns)| ns.iter().try_for_each(|t| f(v, t.to.index(), &t.weight)))
+    }
+}
+
+impl<E, Ix: VertexIndex> EdgeSource for Adjacency<E, Ix> {
+    fn try_for_each_edge<F, Er>(&self, mut f: F) -> Result<(), Er>
+    where
+        F: FnMut(usize, usize, &E) -> Result<(), Er>,
+    {
+        self.neighbourhoods()
+            .try_for_each(|(v, mut ns)| ns.try_for_each(|t| f(v, t.to.index(), &t.weight)))
+    }
+}
+
+fn is_comment(l: &str) -> bool {
+    l.starts_with('#') || l.starts_with('%')
+}
+
+/// The number of vertices declared by a SNAP `# Nodes: n Edges: m` comment.
+fn declared_vertices(comment: &str) -> Option<usize> {
+    let mut words = comment[1..].split_whitespace();
+    words.position(|w| w == "Nodes:")?;
+    words.next()?.parse().ok()
+}
+
+fn parse_edge<E: Weight>(line: usize, content: &str, offset: usize) -> Result<WEdge<E>, Error> {
+    let malformed = || Error::Malformed {
+        line,
+        content: content.to_string(),
+    };
+    let mut columns = content.split_whitespace();
+    let mut vertex = || -> Result<usize, Error> {
+        columns
+            .next()
+            .and_then(|c| usize::from_str(c).ok())
+            .and_then(|v| v.checked_sub(offset))
+            .ok_or_else(malformed)
+    };
+    let from = vertex()?;
+    let to = vertex()?;
+    let weight = E::parse_weight(columns.next()).ok_or_else(|| Error::InvalidWeight {
+        line,
+        content: content.to_string(),
+    })?;
+    Ok((from, to, weight))
+}
+
+fn check_range<E>(line: usize, (from, to, _): &WEdge<E>, vertices: usize) -> Result<(), Error> {
+    match [from, to].iter().find(|&&&v| v >= vertices) {
+        Some(&&vertex) => Err(Error::VertexOutOfRange {
+            line,
+            vertex,
+            vertices,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Reads an edge list. The number of vertices is the one declared by a `# Nodes: n` comment, as
+/// written by [`write_edge_list`], or one more than the biggest vertex id found if that's bigger,
+/// since SNAP counts the vertices of graphs whose ids aren't dense.
+pub fn read_edge_list<G, R>(reader: R) -> Result<G, Error>
+where
+    G: WFromEdges,
+    G::EdgeWeight: Weight,
+    R: BufRead,
+{
+    let (edges, declared) = parse_edge_list(reader, None)?;
+    let n = edges
+        .iter()
+        .map(|e| usize::max(e.0, e.1) + 1)
+        .chain(declared)
+        .max()
+        .unwrap_or(0);
+    Ok(G::from_edges(n, edges))
+}
+
+/// Reads an edge list of a graph with `vertices` vertices, failing if a vertex id doesn't fit.
+pub fn read_edge_list_with_vertices<G, R>(reader: R, vertices: usize) -> Result<G, Error>
+where
+    G: WFromEdges,
+    G::EdgeWeight: Weight,
+    R: BufRead,
+{
+    let (edges, _) = parse_edge_list(reader, Some(vertices))?;
+    Ok(G::from_edges(vertices, edges))
+}
+
+/// The edges of an edge list and the number of vertices its header declares, if any.
+fn parse_edge_list<E, R>(
+    reader: R,
+    vertices: Option<usize>,
+) -> Result<(Vec<WEdge<E>>, Option<usize>), Error>
+where
+    E: Weight,
+    R: BufRead,
+{
+    let mut edges = Vec::new();
+    let mut declared = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let content = line.trim();
+        if content.is_empty() {
+            continue;
+        }
+        if is_comment(content) {
+            declared = declared.or_else(|| declared_vertices(content));
+            continue;
+        }
+        let edge = parse_edge(i + 1, content, 0)?;
+        if let Some(n) = vertices {
+            check_range(i + 1, &edge, n)?;
+        }
+        edges.push(edge);
+    }
+    Ok((edges, declared))
+}
+
+/// Reads a Matrix Market coordinate file. Only square matrices are supported, rows being the
+/// source of an edge and columns the target, and any other shape is an [`Error::NotSquare`].
+pub fn read_matrix_market<G, R>(reader: R) -> Result<G, Error>
+where
+    G: WFromEdges,
+    G::EdgeWeight: Weight + Clone,
+    R: BufRead,
+{
+    let mut lines = reader.lines().enumerate();
+    let header = match lines.next() {
+        Some((_, line)) => line?,
+        None => return Err(Error::InvalidHeader(String::new())),
+    };
+    let symmetric = {
+        let h = header.to_lowercase();
+        let fields = h.split_whitespace().collect::<Vec<_>>();
+        match fields[..] {
+            ["%%matrixmarket", "matrix", "coordinate", _, "general"] => false,
+            ["%%matrixmarket", "matrix", "coordinate", _, "symmetric"] => true,
+            _ => return Err(Error::InvalidHeader(header)),
+        }
+    };
+    let mut size = None;
+    let mut edges = Vec::new();
+    let mut entries = 0;
+    for (i, line) in lines {
+        let line = line?;
+        let content = line.trim();
+        if content.is_empty() || is_comment(content) {
+            continue;
+        }
+        let (n, _) = match size {
+            Some(s) => s,
+            None => {
+                let malformed = || Error::Malformed {
+                    line: i + 1,
+                    content: content.to_string(),
+                };
+                let dims = content
+                    .split_whitespace()
+                    .map(usize::from_str)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| malformed())?;
+                match dims[..] {
+                    [rows, cols, _] if rows != cols => return Err(Error::NotSquare { rows, cols }),
+                    [n, _, nnz] => {
+                        edges.reserve(if symmetric { nnz * 2 } else { nnz });
+                        size = Some((n, nnz));
+                    }
+                    _ => return Err(malformed()),
+                }
+                continue;
+            }
+        };
+        let (from, to, w) = parse_edge::<G::EdgeWeight>(i + 1, content, 1)?;
+        check_range(i + 1, &(from, to, ()), n)?;
+        if symmetric && from != to {
+            edges.push((to, from, w.clone()));
+        }
+        edges.push((from, to, w));
+        entries += 1;
+    }
+    match size {
+        Some((n, nnz)) if nnz == entries => Ok(G::from_edges(n, edges)),
+        Some((_, nnz)) => Err(Error::EntryCountMismatch {
+            expected: nnz,
+            found: entries,
+        }),
+        None => Err(Error::InvalidHeader(header)),
+    }
+}
+
+/// Writes a graph as an edge list, preceded by a comment with its size.
+pub fn write_edge_list<G, W>(g: &G, mut w: W) -> io::Result<()>
+where
+    G: EdgeSource,
+    G::EdgeWeight: Weight,
+    W: Write,
+{
+    writeln!(w, "# Nodes: {} Edges: {}", g.vertices(), g.edges())?;
+    g.try_for_each_edge(|from, to, weight| {
+        write!(w, "{}\t{}", from, to)?;
+        weight.write_weight(&mut w)?;
+        writeln!(w)
+    })
+}
+
+/// Writes a graph as a general Matrix Market coordinate file.
+pub fn write_matrix_market<G, W>(g: &G, mut w: W) -> io::Result<()>
+where
+    G: EdgeSource,
+    G::EdgeWeight: Weight,
+    W: Write,
+{
+    writeln!(
+        w,
+        "%%MatrixMarket matrix coordinate {} general",
+        <G::EdgeWeight as Weight>::FIELD
+    )?;
+    writeln!(w, "{} {} {}", g.vertices(), g.vertices(), g.edges())?;
+    g.try_for_each_edge(|from, to, weight| {
+        write!(w, "{} {}", from + 1, to + 1)?;
+        weight.write_weight(&mut w)?;
+        writeln!(w)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{test_graphs::graph_one, EdgeListGraph, FromEdges};
+
+    fn edges<G: EdgeSource>(g: &G) -> Vec<WEdge<G::EdgeWeight>>
+    where
+        G::EdgeWeight: Clone,
+    {
+        let mut v = Vec::new();
+        g.try_for_each_edge::<_, ()>(|a, b, w| {
+            v.push((a, b, w.clone()));
+            Ok(())
+        })
+        .unwrap();
+        v.sort_by_key(|e| (e.0, e.1));
+        v
+    }
+
+    #[test]
+    fn snap_edge_list() {
+        let text = "# Directed graph (each unordered pair of nodes is saved once)\n\
+                    # FromNodeId\tToNodeId\n\
+                    0\t1\n\
+                    0\t2\n\
+                    \n\
+                    3 \t 1\n";
+        let g: EdgeList = read_edge_list(text.as_bytes()).unwrap();
+        assert_eq!(g.vertices(), 4);
+        assert_eq!(g.as_edges(), [(0, 1, ()), (0, 2, ()), (3, 1, ())]);
+    }
+
+    #[test]
+    fn weighted_edge_list() {
+        let g: Adjacency<f64> = read_edge_list("0 1 0.5\n1 0 2\n".as_bytes()).unwrap();
+        assert_eq!(edges(&g), [(0, 1, 0.5), (1, 0, 2.0)]);
+        assert!(matches!(
+            read_edge_list::<Adjacency<f64>, _>("0 1 0.5\n1 0\n".as_bytes()),
+            Err(Error::InvalidWeight { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(matches!(
+            read_edge_list::<EdgeList, _>("0 1\n0 x\n".as_bytes()),
+            Err(Error::Malformed { line: 2, .. })
+        ));
+        assert!(matches!(
+            read_edge_list_with_vertices::<CSR, _>("0 1\n\n2 5\n".as_bytes(), 5),
+            Err(Error::VertexOutOfRange {
+                line: 3,
+                vertex: 5,
+                vertices: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn matrix_market() {
+        let text = "%%MatrixMarket matrix coordinate integer symmetric\n\
+                    % a comment\n\
+                    3 3 3\n\
+                    1 2 10\n\
+                    3 1 20\n\
+                    2 2 30\n";
+        let g: CSR<i32> = read_matrix_market(text.as_bytes()).unwrap();
+        assert_eq!(g.vertices(), 3);
+        assert_eq!(
+            edges(&g),
+            [(0, 1, 10), (0, 2, 20), (1, 0, 10), (1, 1, 30), (2, 0, 20)]
+        );
+        assert!(matches!(
+            read_matrix_market::<CSR<i32>, _>(&text.as_bytes()[..text.len() - 7]),
+            Err(Error::EntryCountMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            read_matrix_market::<CSR<i32>, _>("%%MatrixMarket matrix array real general\n".as_bytes()),
+            Err(Error::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            read_matrix_market::<CSR, _>(
+                "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n0 1\n".as_bytes()
+            ),
+            Err(Error::Malformed { line: 3, .. })
+        ));
+        assert!(matches!(
+            read_matrix_market::<CSR, _>(
+                "%%MatrixMarket matrix coordinate pattern general\n2 3 1\n1 3\n".as_bytes()
+            ),
+            Err(Error::NotSquare { rows: 2, cols: 3 })
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let g = graph_one::<CSR>();
+        let mut out = Vec::new();
+        write_edge_list(&g, &mut out).unwrap();
+        let el: EdgeList = read_edge_list(&out[..]).unwrap();
+        assert_eq!(edges(&el), edges(&g));
+
+        let mut out = Vec::new();
+        write_matrix_market(&el, &mut out).unwrap();
+        let adj: Adjacency = read_matrix_market(&out[..]).unwrap();
+        assert_eq!(adj.vertices(), g.vertices());
+        assert_eq!(edges(&adj), edges(&g));
+
+        // the header keeps the vertices after the last one with edges
+        let isolated = <EdgeList as FromEdges>::from_edges(5, vec![(0, 1), (2, 1)]);
+        let mut out = Vec::new();
+        write_edge_list(&isolated, &mut out).unwrap();
+        let el: EdgeList = read_edge_list(&out[..]).unwrap();
+        assert_eq!(el.vertices(), 5);
+        assert_eq!(edges(&el), edges(&isolated));
+
+        let g: Adjacency<u32> = read_edge_list("0 1 3\n2 0 4\n".as_bytes()).unwrap();
+        let mut out = Vec::new();
+        write_matrix_market(&g, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "%%MatrixMarket matrix coordinate integer general\n3 3 2\n1 2 3\n3 1 4\n"
+        );
+    }
+}
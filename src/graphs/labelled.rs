@@ -0,0 +1,419 @@
+//! Graphs whose vertices are known by arbitrary labels.
+//!
+//! Datasets name their vertices with urls, sparse integers or any other ids, while every graph
+//! representation expects them to be `0..n`. [`LabelledGraph`] keeps the graph with dense ids
+//! and the bijection between them and the labels, so the output of the algorithms, which is in
+//! dense ids, can be translated back.
+use super::{EdgeListGraph, FromEdges, Graph, HasLink, Successors, WEdge, WFromEdges, WSuccessors};
+use rustc_hash::FxHashMap;
+use std::{
+    fmt::{self, Display},
+    hash::Hash,
+    ops::Deref,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The graph doesn't have one vertex per label.
+    Length { vertices: usize, labels: usize },
+    /// Two vertices were given the same label.
+    DuplicateLabel { first: usize, second: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Length { vertices, labels } => write!(
+                f,
+                "expected {} labels, one per vertex, but got {}",
+                vertices, labels
+            ),
+            Error::DuplicateLabel { first, second } => {
+                write!(f, "vertices {} and {} have the same label", first, second)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A bijection between labels and the dense ids `0..n`, handed out in the order the labels are
+/// first seen.
+#[derive(Debug, Clone)]
+pub struct Labels<L> {
+    labels: Vec<L>,
+    ids: FxHashMap<L, usize>,
+}
+
+impl<L> Default for Labels<L> {
+    fn default() -> Self {
+        Self {
+            labels: Vec::new(),
+            ids: FxHashMap::default(),
+        }
+    }
+}
+
+impl<L: Hash + Eq + Clone> Labels<L> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of `label`, giving it the next one if it doesn't have one yet.
+    pub fn insert(&mut self, label: L) -> usize {
+        let next = self.labels.len();
+        let labels = &mut self.labels;
+        *self.ids.entry(label).or_insert_with_key(|label| {
+            labels.push(label.clone());
+            next
+        })
+    }
+
+    pub fn id(&self, label: &L) -> Option<usize> {
+        self.ids.get(label).copied()
+    }
+}
+
+impl<L> Labels<L> {
+    /// The label of the vertex `id`.
+    pub fn label(&self, id: usize) -> &L {
+        &self.labels[id]
+    }
+
+    /// Every label, indexed by id.
+    pub fn as_slice(&self) -> &[L] {
+        &self.labels
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+impl<L: Hash + Eq + Clone> std::iter::FromIterator<L> for Labels<L> {
+    /// Ids the labels in order, repeated ones keeping their first id.
+    fn from_iter<I: IntoIterator<Item = L>>(labels: I) -> Self {
+        let mut l = Self::new();
+        labels.into_iter().for_each(|label| {
+            l.insert(label);
+        });
+        l
+    }
+}
+
+/// A graph together with the labels of its vertices.
+///
+/// It derefs to the graph, and forwards [`Successors`], [`WSuccessors`], [`HasLink`] and
+/// [`EdgeListGraph`], so the traversals run on it directly, whatever the representation. The
+/// algorithms that need [`RandomAccess`](super::RandomAccess) run on the graph it derefs to.
+/// The graph is never handed out mutably, as that could change its vertices, but the arcs of
+/// an edge list can be reordered in place, which is what
+/// [`karger_stein`](crate::algorithms::min_cut::karger_stein) does.
+#[derive(Debug, Clone)]
+pub struct LabelledGraph<G, L> {
+    graph: G,
+    labels: Labels<L>,
+}
+
+impl<G: Graph, L: Hash + Eq + Clone> LabelledGraph<G, L> {
+    /// Labels the vertices of `graph` with `labels`, in order.
+    pub fn new<I>(graph: G, labels: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = L>,
+    {
+        let mut l = Labels::new();
+        for (v, label) in labels.into_iter().enumerate() {
+            let id = l.insert(label);
+            if id != v {
+                return Err(Error::DuplicateLabel {
+                    first: id,
+                    second: v,
+                });
+            }
+        }
+        if l.len() != graph.vertices() {
+            return Err(Error::Length {
+                vertices: graph.vertices(),
+                labels: l.len(),
+            });
+        }
+        Ok(Self { graph, labels: l })
+    }
+
+    /// Builds the graph out of arcs between labels, giving the labels ids in the order they
+    /// first show up.
+    pub fn from_labelled_edges<I>(edges: I) -> Self
+    where
+        G: WFromEdges,
+        I: IntoIterator<Item = (L, L, G::EdgeWeight)>,
+    {
+        Self::from_labelled_edges_with(Labels::new(), edges)
+    }
+
+    /// Like [`from_labelled_edges`](LabelledGraph::from_labelled_edges), starting from labels
+    /// that already have ids, such as the ones of vertices without arcs.
+    pub fn from_labelled_edges_with<I>(mut labels: Labels<L>, edges: I) -> Self
+    where
+        G: WFromEdges,
+        I: IntoIterator<Item = (L, L, G::EdgeWeight)>,
+    {
+        let edges = edges
+            .into_iter()
+            .map(|(from, to, w)| (labels.insert(from), labels.insert(to), w))
+            .collect::<Vec<WEdge<G::EdgeWeight>>>();
+        Self {
+            graph: G::from_edges(labels.len(), edges),
+            labels,
+        }
+    }
+
+    /// [`from_labelled_edges`](LabelledGraph::from_labelled_edges) for unweighted graphs.
+    pub fn from_labelled_links<I>(links: I) -> Self
+    where
+        G: FromEdges,
+        I: IntoIterator<Item = (L, L)>,
+    {
+        let mut labels = Labels::new();
+        let links = links
+            .into_iter()
+            .map(|(from, to)| (labels.insert(from), labels.insert(to)))
+            .collect::<Vec<_>>();
+        Self {
+            graph: G::from_edges(labels.len(), links),
+            labels,
+        }
+    }
+
+    /// The dense id of the vertex labelled `label`.
+    pub fn id(&self, label: &L) -> Option<usize> {
+        self.labels.id(label)
+    }
+}
+
+impl<G, L> LabelledGraph<G, L> {
+    /// The label of the vertex `id`.
+    pub fn label(&self, id: usize) -> &L {
+        self.labels.label(id)
+    }
+
+    pub fn labels(&self) -> &Labels<L> {
+        &self.labels
+    }
+
+    pub fn graph(&self) -> &G {
+        &self.graph
+    }
+
+    pub fn into_parts(self) -> (G, Labels<L>) {
+        (self.graph, self.labels)
+    }
+
+    /// Translates pairs of vertices, like the edges of a cut, to pairs of labels.
+    pub fn label_edges<I>(&self, edges: I) -> Vec<(&L, &L)>
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        edges
+            .into_iter()
+            .map(|(from, to)| (self.label(from), self.label(to)))
+            .collect()
+    }
+
+    /// Pairs each value of a per vertex result, like distances or centralities, with the label
+    /// of its vertex.
+    pub fn label_values<T, I>(&self, values: I) -> Vec<(&L, T)>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.labels.as_slice().iter().zip(values).collect()
+    }
+}
+
+impl<G, L> Deref for LabelledGraph<G, L> {
+    type Target = G;
+    fn deref(&self) -> &G {
+        &self.graph
+    }
+}
+
+impl<G: Graph, L> Graph for LabelledGraph<G, L> {
+    type EdgeWeight = G::EdgeWeight;
+    fn vertices(&self) -> usize {
+        self.graph.vertices()
+    }
+
+    fn edges(&self) -> usize {
+        self.graph.edges()
+    }
+}
+
+impl<G: Successors, L> Successors for LabelledGraph<G, L> {
+    type Successors<'a>
+        = G::Successors<'a>
+    where
+        Self: 'a;
+
+    fn successors(&self, from: usize) -> Self::Successors<'_> {
+        self.graph.successors(from)
+    }
+}
+
+impl<G: WSuccessors, L> WSuccessors for LabelledGraph<G, L> {
+    type WSuccessors<'a>
+        = G::WSuccessors<'a>
+    where
+        Self: 'a;
+
+    fn weighted_successors(&self, from: usize) -> Self::WSuccessors<'_> {
+        self.graph.weighted_successors(from)
+    }
+}
+
+impl<G: HasLink, L> HasLink for LabelledGraph<G, L> {
+    fn has_link(&self, from: usize, to: usize) -> bool {
+        self.graph.has_link(from, to)
+    }
+}
+
+impl<G: EdgeListGraph, L> EdgeListGraph for LabelledGraph<G, L> {
+    type Index = G::Index;
+    type Edges = G::Edges;
+
+    fn as_edges(&self) -> &[(Self::Index, Self::Index, Self::EdgeWeight)] {
+        self.graph.as_edges()
+    }
+
+    fn as_edges_mut(&mut self) -> &mut [(Self::Index, Self::Index, Self::EdgeWeight)] {
+        self.graph.as_edges_mut()
+    }
+
+    fn into_edges(self) -> Self::Edges {
+        self.graph.into_edges()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::{
+            apl::{bfs, hyper_ball::hyper_ball},
+            components,
+            min_cut::karger_stein,
+            shortest_paths::dijkstra,
+        },
+        graphs::{compressed::CompressedCSR, csr::CSR, edge_list::EdgeList},
+        util::{
+            disjoint_set::PathCompression,
+            hyper_counters::{HyperLogLog, B},
+        },
+    };
+    use std::iter::FromIterator;
+
+    #[test]
+    fn sparse_ids() {
+        let g = LabelledGraph::<CSR<u32>, u64>::from_labelled_edges(vec![
+            (1_000_000, 7, 3),
+            (7, 42, 1),
+            (42, 1_000_000, 2),
+            (7, 1_000_000, 5),
+        ]);
+        assert_eq!(g.vertices(), 3);
+        assert_eq!(g.labels().as_slice(), &[1_000_000, 7, 42]);
+        assert_eq!(g.id(&42), Some(2));
+        assert_eq!(g.id(&8), None);
+        let to = |from: u64| {
+            g.weighted_successors(g.id(&from).unwrap())
+                .map(|w| (*g.label(w.to), w.weight))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(to(7), vec![(42, 1), (1_000_000, 5)]);
+        assert_eq!(to(42), vec![(1_000_000, 2)]);
+    }
+
+    #[test]
+    fn translates_outputs() {
+        let g = LabelledGraph::<CSR<u32>, _>::from_labelled_edges(vec![
+            ("a.com", "b.com", 4),
+            ("b.com", "c.com", 1),
+            ("a.com", "c.com", 7),
+            ("c.com", "a.com", 2),
+        ]);
+        let paths = dijkstra(&g, g.id(&"a.com").unwrap());
+        assert_eq!(
+            g.label_values(paths.distances.iter().copied()),
+            vec![
+                (&"a.com", Some(0)),
+                (&"b.com", Some(4)),
+                (&"c.com", Some(5))
+            ]
+        );
+        let path = paths.path_to(g.id(&"c.com").unwrap()).unwrap();
+        assert_eq!(
+            g.label_edges(path.windows(2).map(|w| (w[0], w[1]))),
+            vec![(&"a.com", &"b.com"), (&"b.com", &"c.com")]
+        );
+
+        // every cut of a path is a single edge
+        let mut path =
+            LabelledGraph::<EdgeList, _>::from_labelled_links(vec![("x", "y"), ("y", "z")]);
+        let cut = karger_stein::<_, PathCompression>(&mut path);
+        let cut = path.label_edges(cut);
+        assert_eq!(cut.len(), 1);
+        let (&a, &b) = cut[0];
+        assert!([("x", "y"), ("y", "x"), ("y", "z"), ("z", "y")].contains(&(a, b)));
+    }
+
+    #[test]
+    fn any_representation() {
+        let links = vec![("a", "b"), ("b", "c"), ("c", "a"), ("d", "e")];
+        let csr = LabelledGraph::<CSR, _>::from_labelled_links(links.clone());
+        let list = LabelledGraph::<EdgeList, _>::from_labelled_links(links.clone());
+        let compressed = LabelledGraph::<CompressedCSR, _>::from_labelled_links(links);
+        let counters = || vec![HyperLogLog::new_with_seed(B::B4, 0xBAD5EED); 5].into_boxed_slice();
+        let expected = bfs::path_lengths(&*csr);
+        assert_eq!(bfs::path_lengths(&list), expected);
+        assert_eq!(bfs::path_lengths(&compressed), expected);
+        assert_eq!(hyper_ball(&list, counters()), hyper_ball(&*csr, counters()));
+        assert_eq!(
+            hyper_ball(&compressed, counters()),
+            hyper_ball(&*csr, counters())
+        );
+        let expected = components::strongly_connected(&*csr);
+        assert_eq!(components::strongly_connected(&list), expected);
+        assert_eq!(components::strongly_connected(&compressed), expected);
+        assert_eq!(expected.count, 3);
+    }
+
+    #[test]
+    fn invalid_labels() {
+        let g = <CSR as FromEdges>::from_edges(3, vec![(0, 1), (1, 2)]);
+        assert_eq!(
+            LabelledGraph::new(g.clone(), vec!["a", "b"]).unwrap_err(),
+            Error::Length {
+                vertices: 3,
+                labels: 2
+            }
+        );
+        assert_eq!(
+            LabelledGraph::new(g.clone(), vec!["a", "b", "a"]).unwrap_err(),
+            Error::DuplicateLabel {
+                first: 0,
+                second: 2
+            }
+        );
+        let labelled = LabelledGraph::new(g, vec!["a", "b", "c"]).unwrap();
+        assert_eq!(labelled.label_edges(vec![(2, 0)]), vec![(&"c", &"a")]);
+        let isolated = Labels::from_iter(vec!["lonely", "a"]);
+        let g = LabelledGraph::<EdgeList<u8>, _>::from_labelled_edges_with(
+            isolated,
+            vec![("a", "b", 1)],
+        );
+        assert_eq!(g.vertices(), 3);
+        assert_eq!(g.as_edges(), &[(1, 2, 1)]);
+    }
+}
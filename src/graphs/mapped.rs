@@ -0,0 +1,477 @@
+//! A binary CSR file that can be memory mapped and used in place.
+//!
+//! The file is a sequence of little endian 64 bit words, so every section is aligned:
+//! ```text
+//! magic           b"AAVACSR\0"
+//! version, weight size in bytes   (two 32 bit words)
+//! vertices
+//! edges
+//! row_indexes     vertices + 1 words
+//! columns         edges words
+//! weights         edges * weight size bytes, only if the weights aren't zero sized
+//! ```
+//! Opening a file maps it and checks its header, its row indexes and its columns once, so a
+//! corrupt file fails to open instead of panicking in a traversal. The graph is used in place
+//! after that, so any number of processes can share the same copy through the page cache.
+use super::{
+    csr::CSR, io::EdgeSource, Graph, HasLink, RandomAccess, Successors, To, VertexIndex,
+    WSuccessors,
+};
+use bytemuck::Pod;
+use memmap2::Mmap;
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufWriter, Write},
+    iter::Map,
+    marker::PhantomData,
+    mem::size_of,
+    ops::Range,
+    path::Path,
+    slice,
+};
+
+const MAGIC: [u8; 8] = *b"AAVACSR\0";
+const VERSION: u32 = 1;
+const HEADER: usize = 32;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    NotAGraph,
+    UnsupportedVersion(u32),
+    /// The weights in the file don't have the size of the requested weight type.
+    WeightSize {
+        expected: usize,
+        found: usize,
+    },
+    /// The file is shorter than its header says it should be.
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+    /// The header describes a graph too large to be addressed.
+    TooLarge,
+    /// The row indexes decrease, or don't go from zero to the number of edges.
+    InvalidRowIndexes,
+    /// An arc reaches a vertex past the last one.
+    InvalidColumn {
+        arc: usize,
+        to: u64,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::NotAGraph => f.write_str("not a binary csr file"),
+            Error::UnsupportedVersion(v) => write!(f, "unsupported binary csr version {}", v),
+            Error::WeightSize { expected, found } => write!(
+                f,
+                "expected weights of {} bytes but the file has weights of {} bytes",
+                expected, found
+            ),
+            Error::Truncated { expected, found } => write!(
+                f,
+                "expected a file of {} bytes but it only has {}",
+                expected, found
+            ),
+            Error::TooLarge => f.write_str("the graph is too large to be addressed"),
+            Error::InvalidRowIndexes => f.write_str("the row indexes are not valid"),
+            Error::InvalidColumn { arc, to } => {
+                write!(
+                    f,
+                    "arc {} reaches vertex {}, which is out of range",
+                    arc, to
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Writes `g` in the binary CSR format.
+pub fn write<G, W>(g: &G, mut w: W) -> io::Result<()>
+where
+    G: RandomAccess,
+    G::EdgeWeight: Pod,
+    W: Write,
+{
+    let weight_size = size_of::<G::EdgeWeight>();
+    w.write_all(&MAGIC)?;
+    w.write_all(&VERSION.to_le_bytes())?;
+    w.write_all(&(weight_size as u32).to_le_bytes())?;
+    w.write_all(&(g.vertices() as u64).to_le_bytes())?;
+    w.write_all(&(g.edges() as u64).to_le_bytes())?;
+    let mut row_index = 0u64;
+    w.write_all(&row_index.to_le_bytes())?;
+    for v in 0..g.vertices() {
+        row_index += g.neighbours(v).len() as u64;
+        w.write_all(&row_index.to_le_bytes())?;
+    }
+    for v in 0..g.vertices() {
+        for n in g.neighbours(v) {
+            w.write_all(&(n.to.index() as u64).to_le_bytes())?;
+        }
+    }
+    if weight_size > 0 {
+        for v in 0..g.vertices() {
+            for n in g.neighbours(v) {
+                w.write_all(bytemuck::bytes_of(&n.weight))?;
+            }
+        }
+    }
+    w.flush()
+}
+
+/// Writes `g` to the file at `path` in the binary CSR format.
+pub fn save<G, P>(g: &G, path: P) -> io::Result<()>
+where
+    G: RandomAccess,
+    G::EdgeWeight: Pod,
+    P: AsRef<Path>,
+{
+    write(g, BufWriter::new(File::create(path)?))
+}
+
+/// A read only CSR backed by a memory mapped binary CSR file.
+///
+/// The weights are read from the file as they are, so `E` must be the type the file was
+/// written with, which is only checked by size.
+pub struct MappedCSR<E = ()> {
+    map: Mmap,
+    vertices: usize,
+    edges: usize,
+    _weight: PhantomData<E>,
+}
+
+fn word(bytes: &[u8], i: usize) -> u64 {
+    let mut w = [0; 8];
+    w.copy_from_slice(&bytes[i * 8..(i + 1) * 8]);
+    u64::from_le_bytes(w)
+}
+
+impl<E: Pod> MappedCSR<E> {
+    /// Maps the binary CSR file at `path`.
+    ///
+    /// The file must not be modified while it's mapped, the graph would change under it.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        // SAFETY: the map is only ever read, and the file is required not to change while it's
+        // mapped, as mapping is the whole point of this format.
+        let map = unsafe { Mmap::map(&file)? };
+        if map.len() < HEADER || map[..8] != MAGIC {
+            return Err(Error::NotAGraph);
+        }
+        let version = word(&map, 1) as u32;
+        let weight_size = (word(&map, 1) >> 32) as usize;
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        if weight_size != size_of::<E>() {
+            return Err(Error::WeightSize {
+                expected: size_of::<E>(),
+                found: weight_size,
+            });
+        }
+        let vertices = usize::try_from(word(&map, 2)).map_err(|_| Error::TooLarge)?;
+        let edges = usize::try_from(word(&map, 3)).map_err(|_| Error::TooLarge)?;
+        let expected = vertices
+            .checked_add(1)
+            .and_then(|words| words.checked_add(edges))
+            .and_then(|words| words.checked_mul(8))
+            .and_then(|bytes| bytes.checked_add(HEADER))
+            .and_then(|bytes| bytes.checked_add(weight_size.checked_mul(edges)?))
+            .ok_or(Error::TooLarge)?;
+        if map.len() < expected {
+            return Err(Error::Truncated {
+                expected,
+                found: map.len(),
+            });
+        }
+        let graph = Self {
+            map,
+            vertices,
+            edges,
+            _weight: PhantomData,
+        };
+        graph.validate()?;
+        Ok(graph)
+    }
+
+    /// Checks that the rows cover the columns in order and that every arc reaches a vertex.
+    fn validate(&self) -> Result<(), Error> {
+        let rows = self.row_indexes();
+        let monotone = rows
+            .windows(2)
+            .all(|w| u64::from_le(w[0]) <= u64::from_le(w[1]));
+        if rows[0] != 0 || !monotone || u64::from_le(rows[self.vertices]) != self.edges as u64 {
+            return Err(Error::InvalidRowIndexes);
+        }
+        let outside = self
+            .columns()
+            .iter()
+            .map(|&to| u64::from_le(to))
+            .enumerate()
+            .find(|&(_, to)| to >= self.vertices as u64);
+        match outside {
+            Some((arc, to)) => Err(Error::InvalidColumn { arc, to }),
+            None => Ok(()),
+        }
+    }
+
+    /// The words of the file from `start`, to be read with `u64::from_le`.
+    fn words(&self, start: usize, len: usize) -> &[u64] {
+        bytemuck::cast_slice(&self.map[HEADER + 8 * start..HEADER + 8 * (start + len)])
+    }
+
+    fn row_indexes(&self) -> &[u64] {
+        self.words(0, self.vertices + 1)
+    }
+
+    fn columns(&self) -> &[u64] {
+        self.words(self.vertices + 1, self.edges)
+    }
+
+    fn weight(&self, arc: usize) -> E {
+        let size = size_of::<E>();
+        if size == 0 {
+            return E::zeroed();
+        }
+        let start = HEADER + 8 * (self.vertices + 1 + self.edges) + arc * size;
+        bytemuck::pod_read_unaligned(&self.map[start..start + size])
+    }
+
+    fn arcs(&self, v: usize) -> Range<usize> {
+        let rows = self.row_indexes();
+        u64::from_le(rows[v]) as usize..u64::from_le(rows[v + 1]) as usize
+    }
+
+    pub fn degree(&self, v: usize) -> usize {
+        self.arcs(v).len()
+    }
+
+    pub fn neighbours(&self, v: usize) -> Neighbours<'_, E> {
+        Neighbours {
+            graph: self,
+            arcs: self.arcs(v),
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = usize> {
+        0..self.vertices
+    }
+
+    /// Reads the whole graph into memory, copying its rows as they are.
+    ///
+    /// # Panics
+    /// If a vertex id doesn't fit in `Ix`.
+    pub fn to_csr<Ix: VertexIndex>(&self) -> CSR<E, Ix> {
+        let row_indexes = self
+            .row_indexes()
+            .iter()
+            .map(|&r| u64::from_le(r) as usize)
+            .collect();
+        let columns = self
+            .columns()
+            .iter()
+            .enumerate()
+            .map(|(arc, &to)| To {
+                to: Ix::new(u64::from_le(to) as usize),
+                weight: self.weight(arc),
+            })
+            .collect();
+        CSR::from_raw_parts(row_indexes, columns)
+    }
+}
+
+impl<E> Graph for MappedCSR<E> {
+    type EdgeWeight = E;
+    fn vertices(&self) -> usize {
+        self.vertices
+    }
+
+    fn edges(&self) -> usize {
+        self.edges
+    }
+}
+
+impl<E: Pod> HasLink for MappedCSR<E> {
+    fn has_link(&self, from: usize, to: usize) -> bool {
+        self.columns()[self.arcs(from)].contains(&(to as u64).to_le())
+    }
+}
+
+impl<E: Pod> Successors for MappedCSR<E> {
+    type Successors<'a>
+        = Map<slice::Iter<'a, u64>, fn(&u64) -> usize>
+    where
+        E: 'a;
+
+    fn successors(&self, from: usize) -> Self::Successors<'_> {
+        self.columns()[self.arcs(from)]
+            .iter()
+            .map(|&w| u64::from_le(w) as usize)
+    }
+}
+
+impl<E: Pod> WSuccessors for MappedCSR<E> {
+    type WSuccessors<'a>
+        = Neighbours<'a, E>
+    where
+        E: 'a;
+
+    fn weighted_successors(&self, from: usize) -> Neighbours<'_, E> {
+        self.neighbours(from)
+    }
+}
+
+/// The arcs leaving a vertex of a [`MappedCSR`], with their weights read out of the file.
+pub struct Neighbours<'g, E> {
+    graph: &'g MappedCSR<E>,
+    arcs: Range<usize>,
+}
+
+impl<E: Pod> Iterator for Neighbours<'_, E> {
+    type Item = To<E>;
+
+    fn next(&mut self) -> Option<To<E>> {
+        let arc = self.arcs.next()?;
+        Some(To {
+            to: u64::from_le(self.graph.columns()[arc]) as usize,
+            weight: self.graph.weight(arc),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.arcs.size_hint()
+    }
+}
+
+impl<E: Pod> ExactSizeIterator for Neighbours<'_, E> {}
+
+impl<E: Pod> EdgeSource for MappedCSR<E> {
+    fn try_for_each_edge<F, Er>(&self, mut f: F) -> Result<(), Er>
+    where
+        F: FnMut(usize, usize, &E) -> Result<(), Er>,
+    {
+        self.nodes()
+            .try_for_each(|v| self.neighbours(v).try_for_each(|t| f(v, t.to, &t.weight)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::apl::bfs,
+        graphs::{csr::CSR, test_graphs, FromEdges, WFromEdges},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::path::PathBuf;
+
+    fn temp(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("aava-{}-{}.csr", name, std::process::id()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp("round-trip");
+        let edges = test_graphs::random_graph(200, 2000, StdRng::seed_from_u64(0xBAD5EED));
+        let g = <CSR<u32> as WFromEdges>::from_edges(
+            200,
+            edges
+                .iter()
+                .enumerate()
+                .map(|(i, &(f, t))| (f, t, i as u32)),
+        );
+        save(&g, &path).unwrap();
+        let mapped = MappedCSR::<u32>::open(&path).unwrap();
+        assert_eq!(mapped.vertices(), 200);
+        assert_eq!(mapped.edges(), 2000);
+        let loaded: CSR<u32> = mapped.to_csr();
+        let narrow: CSR<u32, u32> = mapped.to_csr();
+        for v in g.nodes() {
+            assert!(mapped.neighbours(v).eq(g.neighbours(v).cloned()));
+            assert!(loaded.neighbours(v).eq(g.neighbours(v)));
+            assert!(narrow.weighted_successors(v).eq(g.weighted_successors(v)));
+        }
+        assert!(matches!(
+            MappedCSR::<u64>::open(&path),
+            Err(Error::WeightSize {
+                expected: 8,
+                found: 4
+            })
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unweighted() {
+        let path = temp("unweighted");
+        let g = test_graphs::graph_one::<CSR>();
+        save(&g, &path).unwrap();
+        let mapped = MappedCSR::<()>::open(&path).unwrap();
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len() as usize,
+            HEADER + 8 * (g.vertices() + 1 + g.edges())
+        );
+        assert_eq!(bfs::path_lengths(&mapped), bfs::path_lengths(&g));
+        assert!(mapped.has_link(0, 1));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid() {
+        let path = temp("invalid");
+        std::fs::write(&path, b"not a graph at all, just some text").unwrap();
+        assert!(matches!(
+            MappedCSR::<()>::open(&path),
+            Err(Error::NotAGraph)
+        ));
+        let g = <CSR as FromEdges>::from_edges(3, vec![(0, 1), (1, 2)]);
+        let mut bytes = Vec::new();
+        write(&g, &mut bytes).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(
+            MappedCSR::<()>::open(&path),
+            Err(Error::Truncated { .. })
+        ));
+        let open = |bytes: &[u8]| {
+            std::fs::write(&path, bytes).unwrap();
+            MappedCSR::<()>::open(&path)
+        };
+        let set = |i: usize, w: u64| {
+            let mut bytes = bytes.clone();
+            bytes[i * 8..(i + 1) * 8].copy_from_slice(&w.to_le_bytes());
+            bytes
+        };
+        // a header whose size overflows
+        assert!(matches!(open(&set(3, u64::MAX / 4)), Err(Error::TooLarge)));
+        // the row indexes decrease, or end past the number of edges
+        assert!(matches!(open(&set(5, 3)), Err(Error::InvalidRowIndexes)));
+        assert!(matches!(open(&set(7, 3)), Err(Error::InvalidRowIndexes)));
+        // the arc 1 -> 2 now reaches 3
+        assert!(matches!(
+            open(&set(9, 3)),
+            Err(Error::InvalidColumn { arc: 1, to: 3 })
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,19 +1,24 @@
-use super::{Graph, To, WEdge, WFromEdges, WMutable};
+use super::{
+    csr::CSR,
+    transform::{self, Transform},
+    Graph, To, VertexIndex, WEdge, WFromEdges, WMutable,
+};
 use itertools::Itertools;
 use std::{
     fmt::{self, Debug},
-    ops::Index,
+    ops::{Index, IndexMut},
 };
 
-type Neighbours<E> = Vec<To<E>>;
+type Neighbours<E, Ix> = Vec<To<E, Ix>>;
 
+/// The vertex ids of the arcs are stored as `Ix`, see [`VertexIndex`].
 #[derive(Clone)]
-pub struct Adjacency<E = ()> {
-    matrix: Vec<Neighbours<E>>,
+pub struct Adjacency<E = (), Ix = usize> {
+    matrix: Vec<Neighbours<E, Ix>>,
     n_edges: usize,
 }
 
-impl<E> Graph for Adjacency<E> {
+impl<E, Ix> Graph for Adjacency<E, Ix> {
     type EdgeWeight = E;
     fn vertices(&self) -> usize {
         self.matrix.len()
@@ -24,7 +29,7 @@ impl<E> Graph for Adjacency<E> {
     }
 }
 
-impl<E> WFromEdges for Adjacency<E> {
+impl<E, Ix: VertexIndex> WFromEdges for Adjacency<E, Ix> {
     fn from_edges<I, Iter>(n: usize, list: I) -> Self
     where
         I: IntoIterator<IntoIter = Iter, Item = WEdge<E>>,
@@ -32,7 +37,7 @@ impl<E> WFromEdges for Adjacency<E> {
     {
         let edges = list.into_iter();
         let mut s = Self {
-            matrix: Vec::with_capacity(n),
+            matrix: (0..n).map(|_| Vec::new()).collect(),
             n_edges: 0,
         };
         edges.for_each(|(from, to, w)| {
@@ -42,7 +47,13 @@ impl<E> WFromEdges for Adjacency<E> {
     }
 }
 
-impl<E> Adjacency<E> {
+impl<E, Ix> Default for Adjacency<E, Ix> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, Ix> Adjacency<E, Ix> {
     pub fn new() -> Self {
         Self {
             matrix: Default::default(),
@@ -57,7 +68,19 @@ impl<E> Adjacency<E> {
         }
     }
 
-    pub fn neighbourhoods(&self) -> impl Iterator<Item = (usize, impl Iterator<Item = &To<E>>)> {
+    /// Builds an adjacency list out of the neighbours of each vertex.
+    pub(crate) fn from_rows(matrix: Vec<Neighbours<E, Ix>>) -> Self {
+        let n_edges = matrix.iter().map(Vec::len).sum();
+        Self { matrix, n_edges }
+    }
+
+    pub(crate) fn into_rows(self) -> Vec<Neighbours<E, Ix>> {
+        self.matrix
+    }
+
+    pub fn neighbourhoods(
+        &self,
+    ) -> impl Iterator<Item = (usize, impl Iterator<Item = &To<E, Ix>>)> {
         self.matrix
             .iter()
             .enumerate()
@@ -65,8 +88,9 @@ impl<E> Adjacency<E> {
     }
 }
 
-impl<E> WMutable for Adjacency<E> {
+impl<E, Ix: VertexIndex> WMutable for Adjacency<E, Ix> {
     fn add_weighed_link(&mut self, from: usize, to: usize, weight: E) -> bool {
+        let to = Ix::new(to);
         match self.matrix.get_mut(from) {
             Some(neigh) => neigh.push(To { to, weight }),
             None => {
@@ -79,18 +103,66 @@ impl<E> WMutable for Adjacency<E> {
     }
 
     fn add_vertex(&mut self, from: usize) {
-        self.matrix.resize_with(from + 1, Default::default);
+        if from >= self.matrix.len() {
+            self.matrix.resize_with(from + 1, Default::default);
+        }
+    }
+}
+
+impl<E, Ix: VertexIndex> Transform for Adjacency<E, Ix> {
+    fn transpose(&self) -> Self
+    where
+        E: Clone,
+    {
+        let (row_indexes, columns) = transform::transposed(self);
+        CSR::from_raw_parts(row_indexes, columns).into()
+    }
+
+    fn symmetrize<F>(&self, merge: F) -> Self
+    where
+        E: Clone,
+        F: FnMut(E, E) -> E,
+    {
+        let (row_indexes, columns) = transform::symmetrized(self, merge);
+        CSR::from_raw_parts(row_indexes, columns).into()
+    }
+
+    fn remove_self_loops(&mut self) {
+        for (from, neighbours) in self.matrix.iter_mut().enumerate() {
+            neighbours.retain(|w| w.to.index() != from);
+        }
+        self.n_edges = self.matrix.iter().map(Vec::len).sum();
+    }
+
+    fn dedup_edges<F>(&mut self, mut merge: F)
+    where
+        F: FnMut(E, E) -> E,
+    {
+        let mut row = Vec::new();
+        for neighbours in &mut self.matrix {
+            row.extend(neighbours.drain(..).map(|w| ((), w)));
+            transform::push_deduped(neighbours, &mut row, &mut merge);
+        }
+        self.n_edges = self.matrix.iter().map(Vec::len).sum();
     }
 }
 
-impl<E> Index<usize> for Adjacency<E> {
-    type Output = [To<E>];
+impl<E, Ix> Index<usize> for Adjacency<E, Ix> {
+    type Output = [To<E, Ix>];
     fn index(&self, u: usize) -> &Self::Output {
         &self.matrix[u]
     }
 }
 
-impl Debug for Adjacency {
+impl<E, Ix> IndexMut<usize> for Adjacency<E, Ix> {
+    fn index_mut(&mut self, u: usize) -> &mut Self::Output {
+        &mut self.matrix[u]
+    }
+}
+
+super::impl_indexed_graph!(Adjacency);
+
+impl<Ix: Debug> Debug for Adjacency<(), Ix> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.neighbourhoods()
             .try_for_each(|(i, s)| writeln!(f, "{:?}: {:?}", i, s.format(" -> ")))
@@ -0,0 +1,228 @@
+//! Graphs whose neighbours are kept sorted by id.
+//!
+//! [`Sorted`] wraps a [`CSR`](super::csr::CSR) or an [`Adjacency`](super::matrix::Adjacency)
+//! and makes the order part of its type, so [`has_link`](RandomAccess::has_link) can use a
+//! binary search and neighbourhoods can be intersected in a single merge-like pass.
+use super::{Graph, HasLink, RandomAccess, To, VertexIndex, WEdge, WFromEdges, WMutable};
+use std::ops::{Deref, IndexMut};
+
+/// A graph whose neighbour lists are sorted by id, the arcs to the same vertex keeping their
+/// relative order.
+///
+/// It derefs to the wrapped graph, but never mutably, as that could break the order.
+#[derive(Clone, Debug, Default)]
+pub struct Sorted<G>(G);
+
+impl<G> Sorted<G>
+where
+    G: RandomAccess + IndexMut<usize, Output = [To<<G as Graph>::EdgeWeight, G::Index>]>,
+{
+    /// Sorts the neighbours of every vertex of `g`.
+    pub fn new(mut g: G) -> Self {
+        for v in 0..g.vertices() {
+            g[v].sort_by_key(|w| w.to);
+        }
+        Self(g)
+    }
+}
+
+impl<G> Sorted<G> {
+    pub fn into_inner(self) -> G {
+        self.0
+    }
+}
+
+impl<G: RandomAccess> Sorted<G> {
+    /// The vertices `u` and `v` both have an arc to, once each, in increasing order.
+    pub fn common_neighbours(
+        &self,
+        u: usize,
+        v: usize,
+    ) -> CommonNeighbours<'_, G::EdgeWeight, G::Index> {
+        CommonNeighbours {
+            a: self.0.neighbours(u),
+            b: self.0.neighbours(v),
+        }
+    }
+}
+
+impl<G> Deref for Sorted<G> {
+    type Target = G;
+    fn deref(&self) -> &G {
+        &self.0
+    }
+}
+
+impl<G: Graph> Graph for Sorted<G> {
+    type EdgeWeight = G::EdgeWeight;
+    fn vertices(&self) -> usize {
+        self.0.vertices()
+    }
+
+    fn edges(&self) -> usize {
+        self.0.edges()
+    }
+}
+
+impl<G: RandomAccess> RandomAccess for Sorted<G> {
+    type Index = G::Index;
+
+    fn neighbours(&self, from: usize) -> &[To<Self::EdgeWeight, G::Index>] {
+        self.0.neighbours(from)
+    }
+}
+
+impl<G: RandomAccess> HasLink for Sorted<G> {
+    fn has_link(&self, from: usize, to: usize) -> bool {
+        self.0
+            .neighbours(from)
+            .binary_search_by_key(&to, |w| w.to.index())
+            .is_ok()
+    }
+}
+
+impl<G> WFromEdges for Sorted<G>
+where
+    G: WFromEdges
+        + RandomAccess
+        + IndexMut<usize, Output = [To<<G as Graph>::EdgeWeight, G::Index>]>,
+{
+    fn from_edges<I, Iter>(n: usize, list: I) -> Self
+    where
+        I: IntoIterator<IntoIter = Iter, Item = WEdge<Self::EdgeWeight>>,
+        Iter: ExactSizeIterator<Item = WEdge<Self::EdgeWeight>>,
+    {
+        Self::new(G::from_edges(n, list))
+    }
+}
+
+impl<G> WMutable for Sorted<G>
+where
+    G: WMutable + RandomAccess + IndexMut<usize, Output = [To<<G as Graph>::EdgeWeight, G::Index>]>,
+{
+    /// Adds the arc after the others to `to`, the wrapped graph having to add it at the end of
+    /// the neighbours of `from`, as both `CSR` and `Adjacency` do.
+    fn add_weighed_link(&mut self, from: usize, to: usize, w: Self::EdgeWeight) -> bool {
+        if !self.0.add_weighed_link(from, to, w) {
+            return false;
+        }
+        let row = &mut self.0[from];
+        let last = row.len() - 1;
+        let position = row[..last].partition_point(|w| w.to.index() <= to);
+        row[position..].rotate_right(1);
+        true
+    }
+
+    fn add_vertex(&mut self, v: usize) {
+        self.0.add_vertex(v)
+    }
+}
+
+/// The first position in `row` with an arc to `to` or a later vertex, searching exponentially
+/// from the start, so skipping `k` arcs takes `O(log k)` steps.
+fn gallop<E, Ix: VertexIndex>(row: &[To<E, Ix>], to: usize) -> usize {
+    let mut bound = 1;
+    while bound < row.len() && row[bound - 1].to.index() < to {
+        bound *= 2;
+    }
+    let start = bound / 2;
+    let end = usize::min(bound, row.len());
+    start + row[start..end].partition_point(|w| w.to.index() < to)
+}
+
+/// The intersection of two sorted neighbourhoods, see [`Sorted::common_neighbours`].
+///
+/// Each side gallops over the arcs smaller than the head of the other, so intersecting a short
+/// neighbourhood with a long one takes time proportional to the short one, times a logarithm.
+pub struct CommonNeighbours<'g, E, Ix = usize> {
+    a: &'g [To<E, Ix>],
+    b: &'g [To<E, Ix>],
+}
+
+impl<'g, E, Ix: VertexIndex> Iterator for CommonNeighbours<'g, E, Ix> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let (x, y) = (self.a.first()?.to.index(), self.b.first()?.to.index());
+            if x < y {
+                self.a = &self.a[gallop(self.a, y)..];
+            } else if y < x {
+                self.b = &self.b[gallop(self.b, x)..];
+            } else {
+                self.a = &self.a[gallop(self.a, x + 1)..];
+                self.b = &self.b[gallop(self.b, x + 1)..];
+                return Some(x);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{csr::CSR, matrix::Adjacency, test_graphs, FromEdges, Mutable};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn gallops() {
+        let row = [1, 1, 3, 4, 4, 4, 9, 12]
+            .iter()
+            .map(|&to| To { to, weight: () })
+            .collect::<Vec<_>>();
+        for to in 0..14 {
+            assert_eq!(gallop(&row, to), row.iter().filter(|w| w.to < to).count());
+        }
+        assert_eq!(gallop::<(), usize>(&[], 3), 0);
+    }
+
+    #[test]
+    fn stays_sorted() {
+        let mut g: Sorted<CSR> = crate::graph![Sorted<CSR> = (4) {
+            0 => 3; 0 => 1; 0 => 2; 0 => 1; 1 => 0; 2 => 3;
+        }];
+        g.add_link(0, 0);
+        g.add_link(0, 2);
+        g.add_link(5, 1);
+        let row = g.neighbours(0).iter().map(|w| w.to).collect::<Vec<_>>();
+        assert_eq!(row, vec![0, 1, 1, 2, 2, 3]);
+        assert!(g.has_link(0, 3));
+        assert!(!g.has_link(2, 1));
+        assert!(g.has_link(5, 1));
+        assert_eq!(g.vertices(), 6);
+        assert_eq!(g.common_neighbours(0, 2).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn matches_unsorted() {
+        let n = 100;
+        let adj = test_graphs::clustered::<Adjacency, _>(n, 6, 3, StdRng::seed_from_u64(0xBAD5EED));
+        let sorted = Sorted::new(adj.clone());
+        let generated = test_graphs::clustered::<Sorted<Adjacency>, _>(
+            n,
+            6,
+            3,
+            StdRng::seed_from_u64(0xBAD5EED),
+        );
+        assert!((0..n).all(|v| generated
+            .neighbours(v)
+            .windows(2)
+            .all(|w| w[0].to <= w[1].to)));
+        for u in 0..n {
+            for v in 0..n {
+                assert_eq!(sorted.has_link(u, v), adj.has_link(u, v));
+                let expected = (0..n)
+                    .filter(|&w| adj.has_link(u, w) && adj.has_link(v, w))
+                    .collect::<Vec<_>>();
+                assert_eq!(sorted.common_neighbours(u, v).collect::<Vec<_>>(), expected);
+            }
+        }
+        let csr = <Sorted<CSR> as FromEdges>::from_edges(
+            n,
+            adj.neighbourhoods()
+                .flat_map(|(f, tos)| tos.map(move |t| (f, t.to)))
+                .collect::<Vec<_>>(),
+        );
+        assert!((0..n).all(|v| csr.neighbours(v) == sorted.neighbours(v)));
+    }
+}
@@ -0,0 +1,276 @@
+//! Parts of a graph, either as views that filter its arcs as they are traversed or copied out
+//! into a graph of their own.
+//!
+//! A [`Subgraph`] keeps the vertex ids of the graph it's a view of, the vertices it leaves out
+//! being there without any arcs, so the results of running an algorithm on it line up with the
+//! original graph. A view looks up arcs with [`HasLink`] too, so the clustering coefficient can
+//! be sampled on it. [`induced_subgraph`] copies the arcs between some vertices into a new graph,
+//! renumbering them, which is what the algorithms that need [`RandomAccess`](super::RandomAccess)
+//! have to run on.
+use super::{Graph, HasLink, Successors, To, WEdge, WFromEdges, WSuccessors};
+
+/// A view of the arcs of a graph for which `keep(from, to)` holds.
+pub struct Subgraph<'g, G, F> {
+    graph: &'g G,
+    keep: F,
+    n_edges: usize,
+}
+
+impl<'g, G, F> Subgraph<'g, G, F>
+where
+    G: Successors,
+    F: Fn(usize, usize) -> bool,
+{
+    /// Filters the arcs of `graph` with `keep`, going through them once to count the ones kept.
+    pub fn with_arcs(graph: &'g G, keep: F) -> Self {
+        let n_edges = (0..graph.vertices())
+            .map(|v| graph.successors(v).filter(|&w| keep(v, w)).count())
+            .sum();
+        Self {
+            graph,
+            keep,
+            n_edges,
+        }
+    }
+}
+
+impl<'g, G: Successors> Subgraph<'g, G, ()> {
+    /// The arcs of `graph` between the vertices set in `mask`.
+    pub fn with_vertices(
+        graph: &'g G,
+        mask: &'g [bool],
+    ) -> Subgraph<'g, G, impl Fn(usize, usize) -> bool + 'g> {
+        assert_eq!(mask.len(), graph.vertices(), "one mask entry per vertex");
+        Subgraph::with_arcs(graph, move |from, to| mask[from] && mask[to])
+    }
+}
+
+impl<G, F> Subgraph<'_, G, F> {
+    /// The graph this is a view of.
+    pub fn graph(&self) -> &G {
+        self.graph
+    }
+}
+
+impl<G: Graph, F> Graph for Subgraph<'_, G, F> {
+    type EdgeWeight = G::EdgeWeight;
+    fn vertices(&self) -> usize {
+        self.graph.vertices()
+    }
+
+    fn edges(&self) -> usize {
+        self.n_edges
+    }
+}
+
+impl<G, F> Successors for Subgraph<'_, G, F>
+where
+    G: Successors,
+    F: Fn(usize, usize) -> bool,
+{
+    type Successors<'a>
+        = Kept<'a, G::Successors<'a>, F>
+    where
+        Self: 'a;
+
+    fn successors(&self, from: usize) -> Self::Successors<'_> {
+        Kept {
+            arcs: self.graph.successors(from),
+            from,
+            keep: &self.keep,
+        }
+    }
+}
+
+impl<G, F> WSuccessors for Subgraph<'_, G, F>
+where
+    G: WSuccessors,
+    F: Fn(usize, usize) -> bool,
+{
+    type WSuccessors<'a>
+        = Kept<'a, G::WSuccessors<'a>, F>
+    where
+        Self: 'a;
+
+    fn weighted_successors(&self, from: usize) -> Self::WSuccessors<'_> {
+        Kept {
+            arcs: self.graph.weighted_successors(from),
+            from,
+            keep: &self.keep,
+        }
+    }
+}
+
+impl<G, F> HasLink for Subgraph<'_, G, F>
+where
+    G: HasLink,
+    F: Fn(usize, usize) -> bool,
+{
+    fn has_link(&self, from: usize, to: usize) -> bool {
+        (self.keep)(from, to) && self.graph.has_link(from, to)
+    }
+}
+
+/// The arcs leaving a vertex of a [`Subgraph`].
+pub struct Kept<'a, I, F> {
+    arcs: I,
+    from: usize,
+    keep: &'a F,
+}
+
+/// The items a graph lists its arcs as, [`Subgraph`] filtering them by the vertex they lead to.
+pub trait Target {
+    fn target(&self) -> usize;
+}
+
+impl Target for usize {
+    fn target(&self) -> usize {
+        *self
+    }
+}
+
+impl<E> Target for To<E> {
+    fn target(&self) -> usize {
+        self.to
+    }
+}
+
+impl<I, F> Iterator for Kept<'_, I, F>
+where
+    I: Iterator,
+    I::Item: Target,
+    F: Fn(usize, usize) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let (from, keep) = (self.from, self.keep);
+        self.arcs.find(|a| keep(from, a.target()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.arcs.size_hint().1)
+    }
+}
+
+/// Copies the arcs of `g` between `vertices` into a new graph, `vertices[i]` becoming vertex
+/// `i`, keeping their weights and order.
+///
+/// # Panics
+/// If a vertex is listed twice, or isn't a vertex of `g`.
+pub fn induced_subgraph<G, H>(g: &G, vertices: &[usize]) -> H
+where
+    G: WSuccessors,
+    H: WFromEdges<EdgeWeight = G::EdgeWeight>,
+{
+    let n = g.vertices();
+    let mut ids = vec![usize::MAX; n];
+    for (i, &v) in vertices.iter().enumerate() {
+        assert!(v < n, "vertex {} in a graph of {} vertices", v, n);
+        assert_eq!(ids[v], usize::MAX, "vertex {} is listed twice", v);
+        ids[v] = i;
+    }
+    let edges = vertices
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &v)| {
+            let ids = &ids;
+            g.weighted_successors(v)
+                .filter(move |w| ids[w.to] != usize::MAX)
+                .map(move |w| (i, ids[w.to], w.weight))
+        })
+        .collect::<Vec<WEdge<G::EdgeWeight>>>();
+    H::from_edges(vertices.len(), edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::{
+            apl::{bfs, hyper_ball::hyper_ball},
+            clustering_coef::{c_coef, exact::triangles},
+        },
+        graphs::{compressed::CompressedCSR, csr::CSR, test_graphs, FromEdges},
+        util::hyper_counters::{HyperLogLog, B},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn views() {
+        let g = <CSR<u32> as WFromEdges>::from_edges(
+            4,
+            vec![(0, 1, 1), (1, 2, 2), (2, 0, 3), (2, 3, 4), (3, 3, 5)],
+        );
+        let mask = [true, true, true, false];
+        let triangle = Subgraph::with_vertices(&g, &mask);
+        assert_eq!(triangle.vertices(), 4);
+        assert_eq!(triangle.edges(), 3);
+        assert_eq!(triangle.successors(2).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(triangle.successors(3).count(), 0);
+        let heavy = Subgraph::with_arcs(&g, |from, to| from + to >= 3);
+        assert_eq!(heavy.edges(), 3);
+        assert_eq!(
+            heavy
+                .weighted_successors(2)
+                .map(|w| w.weight)
+                .collect::<Vec<_>>(),
+            vec![4]
+        );
+        let compressed = CompressedCSR::from_csr(&g);
+        let view = Subgraph::with_vertices(&compressed, &mask);
+        assert_eq!(view.successors(0).collect::<Vec<_>>(), vec![1]);
+        assert!(view.has_link(2, 0));
+        assert!(compressed.has_link(2, 3) && !view.has_link(2, 3));
+        assert!(!compressed.has_link(3, 2));
+    }
+
+    #[test]
+    fn masked_algorithms_match_induced() {
+        let g = test_graphs::clustered::<CSR, _>(200, 4, 2, StdRng::seed_from_u64(0xBAD5EED));
+        let vertices = (0..200).filter(|v| v % 3 != 0).collect::<Vec<_>>();
+        let mut mask = vec![false; 200];
+        vertices.iter().for_each(|&v| mask[v] = true);
+        let view = Subgraph::with_vertices(&g, &mask);
+        let induced: CSR = induced_subgraph(&g, &vertices);
+        assert_eq!(induced.vertices(), vertices.len());
+        assert_eq!(induced.edges(), view.edges());
+        for (i, &v) in vertices.iter().enumerate() {
+            let relabelled = induced[i]
+                .iter()
+                .map(|w| vertices[w.to])
+                .collect::<Vec<_>>();
+            assert_eq!(relabelled, view.successors(v).collect::<Vec<_>>());
+        }
+        // the left out vertices don't reach anything
+        assert_eq!(bfs::path_lengths(&view), bfs::path_lengths(&induced));
+        let counters = |n| vec![HyperLogLog::new_with_seed(B::B6, 0xBAD5EED); n].into_boxed_slice();
+        let copy = <CSR as FromEdges>::from_edges(
+            200,
+            (0..200)
+                .flat_map(|v| view.successors(v).map(move |w| (v, w)))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            hyper_ball(&view, counters(200)),
+            hyper_ball(&copy, counters(200))
+        );
+        assert_eq!(triangles(&view).total(), triangles(&induced).total());
+        let rng = || StdRng::seed_from_u64(0xBAD5EED);
+        assert_eq!(c_coef(1000, &view, rng()), c_coef(1000, &copy, rng()));
+    }
+
+    #[test]
+    #[should_panic(expected = "vertex 3 in a graph of 3 vertices")]
+    fn missing_vertex() {
+        let g = <CSR as FromEdges>::from_edges(3, vec![(0, 1), (1, 2)]);
+        let _: CSR = induced_subgraph(&g, &[1, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "vertex 1 is listed twice")]
+    fn repeated_vertex() {
+        let g = <CSR as FromEdges>::from_edges(3, vec![(0, 1), (1, 2)]);
+        let _: CSR = induced_subgraph(&g, &[1, 0, 1]);
+    }
+}
@@ -0,0 +1,282 @@
+//! Operations that rebuild the arcs of a graph.
+//!
+//! All representations store directed arcs, so an undirected graph needs both directions of
+//! each edge, which [`Transform::symmetrize`] adds.
+use super::{Graph, RandomAccess, To, VertexIndex};
+
+pub trait Transform: Graph + Sized {
+    /// The graph with every arc reversed.
+    fn transpose(&self) -> Self
+    where
+        Self::EdgeWeight: Clone;
+
+    /// The graph with both directions of every arc, and no repeated arcs.
+    ///
+    /// The weights of the arcs between the same pair of vertices are combined with `merge`,
+    /// which receives them in a fixed order, the ones leaving the smaller vertex first, so both
+    /// directions end up with the same weight even if `merge` isn't commutative.
+    fn symmetrize<F>(&self, merge: F) -> Self
+    where
+        Self::EdgeWeight: Clone,
+        F: FnMut(Self::EdgeWeight, Self::EdgeWeight) -> Self::EdgeWeight;
+
+    fn remove_self_loops(&mut self);
+
+    /// Replaces the arcs between the same ordered pair of vertices with a single one, combining
+    /// their weights with `merge` in their original order.
+    fn dedup_edges<F>(&mut self, merge: F)
+    where
+        F: FnMut(Self::EdgeWeight, Self::EdgeWeight) -> Self::EdgeWeight;
+}
+
+/// The row boundaries and arcs of a graph laid out like a CSR.
+pub(crate) type Rows<E, Ix> = (Box<[usize]>, Vec<To<E, Ix>>);
+
+/// The rows of the transpose of `g`, laid out like a CSR, using a counting sort over the
+/// targets of the arcs. The entry for the arc `v -> w.to` is `arc(v, w)`, so the predecessors
+/// in each row are in increasing order.
+pub(crate) fn transposed_with<G, T, F>(g: &G, mut arc: F) -> (Box<[usize]>, Vec<T>)
+where
+    G: RandomAccess,
+    F: FnMut(usize, &To<G::EdgeWeight, G::Index>) -> T,
+{
+    let n = g.vertices();
+    let mut row_indexes = vec![0; n + 1].into_boxed_slice();
+    for v in 0..n {
+        for w in g.neighbours(v) {
+            row_indexes[w.to.index() + 1] += 1;
+        }
+    }
+    for i in 0..n {
+        row_indexes[i + 1] += row_indexes[i];
+    }
+    let mut next = row_indexes[..n].to_vec();
+    let mut slots = Vec::with_capacity(row_indexes[n]);
+    slots.resize_with(row_indexes[n], || None);
+    for v in 0..n {
+        for w in g.neighbours(v) {
+            slots[next[w.to.index()]] = Some(arc(v, w));
+            next[w.to.index()] += 1;
+        }
+    }
+    let columns = slots
+        .into_iter()
+        .map(|s| s.expect("every slot is filled once"))
+        .collect();
+    (row_indexes, columns)
+}
+
+/// The rows of the transpose of `g`, see [`transposed_with`].
+pub(crate) fn transposed<G>(g: &G) -> Rows<G::EdgeWeight, G::Index>
+where
+    G: RandomAccess,
+    G::EdgeWeight: Clone,
+{
+    transposed_with(g, |v, w| To {
+        to: G::Index::new(v),
+        weight: w.weight.clone(),
+    })
+}
+
+/// Appends `row` to `columns`, sorted by target and with the weights of the arcs to the same
+/// target merged in the order they had in `row`. The keys put the arcs of each target in the
+/// order `merge` receives them.
+pub(crate) fn push_deduped<E, Ix, K, F>(
+    columns: &mut Vec<To<E, Ix>>,
+    row: &mut Vec<(K, To<E, Ix>)>,
+    merge: &mut F,
+) where
+    Ix: Ord,
+    K: Ord,
+    F: FnMut(E, E) -> E,
+{
+    row.sort_by(|(a, x), (b, y)| x.to.cmp(&y.to).then_with(|| a.cmp(b)));
+    let start = columns.len();
+    for (_, w) in row.drain(..) {
+        match columns[start..].last_mut() {
+            Some(last) if last.to == w.to => {
+                let merged = columns.pop().unwrap();
+                columns.push(To {
+                    to: w.to,
+                    weight: merge(merged.weight, w.weight),
+                });
+            }
+            _ => columns.push(w),
+        }
+    }
+}
+
+/// The rows of the symmetrized version of `g`, laid out like a CSR.
+pub(crate) fn symmetrized<G, F>(g: &G, mut merge: F) -> Rows<G::EdgeWeight, G::Index>
+where
+    G: RandomAccess,
+    G::EdgeWeight: Clone,
+    F: FnMut(G::EdgeWeight, G::EdgeWeight) -> G::EdgeWeight,
+{
+    let n = g.vertices();
+    let (t_rows, t_columns) = transposed(g);
+    let mut row_indexes = Vec::with_capacity(n + 1);
+    let mut columns = Vec::with_capacity(g.edges() + t_columns.len());
+    let mut row = Vec::new();
+    row_indexes.push(0);
+    for v in 0..n {
+        // The key is false for the weights of the arcs leaving the smaller vertex, so they
+        // come first, and loops aren't added twice
+        row.extend(
+            g.neighbours(v)
+                .iter()
+                .map(|w| (w.to.index() < v, w.clone())),
+        );
+        row.extend(
+            t_columns[t_rows[v]..t_rows[v + 1]]
+                .iter()
+                .filter(|w| w.to.index() != v)
+                .map(|w| (w.to.index() > v, w.clone())),
+        );
+        push_deduped(&mut columns, &mut row, &mut merge);
+        row_indexes.push(columns.len());
+    }
+    (row_indexes.into_boxed_slice(), columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::{
+        csr::CSR, edge_list::EdgeList, matrix::Adjacency, EdgeListGraph, WFromEdges,
+    };
+
+    const EDGES: [(usize, usize, u32); 6] = [
+        (0, 1, 1),
+        (1, 0, 2),
+        (1, 2, 3),
+        (2, 2, 4),
+        (0, 1, 5),
+        (3, 1, 6),
+    ];
+
+    /// The arcs of a graph as a sorted list, to compare representations.
+    fn arcs<G: RandomAccess<EdgeWeight = u32>>(g: &G) -> Vec<(usize, usize, u32)> {
+        let mut arcs = (0..g.vertices())
+            .flat_map(|v| {
+                g.neighbours(v)
+                    .iter()
+                    .map(move |w| (v, w.to.index(), w.weight))
+            })
+            .collect::<Vec<_>>();
+        arcs.sort_unstable();
+        arcs
+    }
+
+    fn sorted<Ix: VertexIndex>(g: EdgeList<u32, Ix>) -> Vec<(usize, usize, u32)> {
+        let mut arcs = g
+            .into_edges()
+            .into_iter()
+            .map(|(from, to, w)| (from.index(), to.index(), w))
+            .collect::<Vec<_>>();
+        arcs.sort_unstable();
+        arcs
+    }
+
+    fn check<G>(g: G)
+    where
+        G: Transform<EdgeWeight = u32> + RandomAccess + Clone,
+    {
+        assert_eq!(
+            arcs(&g.transpose()),
+            vec![
+                (0, 1, 2),
+                (1, 0, 1),
+                (1, 0, 5),
+                (1, 3, 6),
+                (2, 1, 3),
+                (2, 2, 4)
+            ]
+        );
+        assert_eq!(
+            arcs(&g.symmetrize(|a, b| a + b)),
+            vec![
+                (0, 1, 8),
+                (1, 0, 8),
+                (1, 2, 3),
+                (1, 3, 6),
+                (2, 1, 3),
+                (2, 2, 4),
+                (3, 1, 6)
+            ]
+        );
+        let mut h = g.clone();
+        h.remove_self_loops();
+        assert_eq!(h.edges(), 5);
+        h.dedup_edges(|a, b| a + b);
+        assert_eq!(arcs(&h), vec![(0, 1, 6), (1, 0, 2), (1, 2, 3), (3, 1, 6)]);
+        assert_eq!(h.edges(), 4);
+    }
+
+    #[test]
+    fn csr() {
+        check(<CSR<u32> as WFromEdges>::from_edges(
+            4,
+            EDGES.iter().copied(),
+        ));
+    }
+
+    #[test]
+    fn narrow_ids() {
+        check(<CSR<u32, u32> as WFromEdges>::from_edges(
+            4,
+            EDGES.iter().copied(),
+        ));
+        check(<Adjacency<u32, u16> as WFromEdges>::from_edges(
+            4,
+            EDGES.iter().copied(),
+        ));
+        let narrow = <EdgeList<u32, u16> as WFromEdges>::from_edges(4, EDGES.iter().copied());
+        let g = <EdgeList<u32> as WFromEdges>::from_edges(4, EDGES.iter().copied());
+        assert_eq!(sorted(narrow.transpose()), sorted(g.transpose()));
+        assert_eq!(
+            sorted(narrow.symmetrize(|a, b| a + b)),
+            sorted(g.symmetrize(|a, b| a + b))
+        );
+        let (mut narrow, mut g) = (narrow, g);
+        narrow.dedup_edges(|a, b| a + b);
+        g.dedup_edges(|a, b| a + b);
+        assert_eq!(sorted(narrow), sorted(g));
+    }
+
+    #[test]
+    fn adjacency() {
+        check(<Adjacency<u32> as WFromEdges>::from_edges(
+            4,
+            EDGES.iter().copied(),
+        ));
+    }
+
+    #[test]
+    fn merge_order() {
+        let merge = |a, b| a * 10 + b;
+        let adj = <Adjacency<u32> as WFromEdges>::from_edges(4, EDGES.iter().copied());
+        let g = <EdgeList<u32> as WFromEdges>::from_edges(4, EDGES.iter().copied());
+        // the arcs from 0 to 1 come before the one from 1 to 0, in both directions
+        let symmetric = arcs(&adj.symmetrize(merge));
+        assert_eq!(symmetric[..2], [(0, 1, 152), (1, 0, 152)]);
+        assert_eq!(sorted(g.symmetrize(merge)), symmetric);
+        let (mut g, mut adj) = (g, adj);
+        g.dedup_edges(merge);
+        adj.dedup_edges(merge);
+        assert_eq!(g.edges(), 5);
+        assert_eq!(sorted(g), arcs(&adj));
+    }
+
+    #[test]
+    fn edge_list() {
+        let g = <EdgeList<u32> as WFromEdges>::from_edges(4, EDGES.iter().copied());
+        let csr = <CSR<u32> as WFromEdges>::from_edges(4, EDGES.iter().copied());
+        assert_eq!(sorted(g.transpose()), arcs(&csr.transpose()));
+        let (mut g, mut csr) = (g, csr);
+        g.remove_self_loops();
+        csr.remove_self_loops();
+        assert_eq!(g.edges(), 5);
+        assert_eq!(sorted(g), arcs(&csr));
+    }
+}
@@ -0,0 +1,816 @@
+//! Reader for graphs stored in the [WebGraph](http://webgraph.di.unimi.it/) `BVGraph` format.
+//!
+//! A `BVGraph` is made of a `basename.properties` file, describing how the graph was compressed,
+//! and a `basename.graph` bit stream holding the successor lists. The successor lists are decoded
+//! sequentially, so the `.offsets` file is not needed.
+//!
+//! Each successor list of a node `x` is encoded as:
+//! ```text
+//! outdegree
+//! reference                   (only if the window size is > 0)
+//! block count, blocks         (only if reference > 0)
+//! interval count, intervals   (only if there are still successors left and intervals are on)
+//! residuals
+//! ```
+use super::{csr::CSR, To, VertexIndex};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+    str::FromStr,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    MissingProperty(&'static str),
+    InvalidProperty { key: &'static str, value: String },
+    UnsupportedGraphClass(String),
+    UnsupportedCode(String),
+    UnexpectedEof,
+    CodeTooLong,
+    InvalidSuccessor { node: usize, successor: i64 },
+    OutdegreeMismatch { node: usize, outdegree: usize, found: usize },
+    ArcCountMismatch { expected: usize, found: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::MissingProperty(k) => write!(f, "missing property '{}'", k),
+            Error::InvalidProperty { key, value } => {
+                write!(f, "invalid value '{}' for property '{}'", value, key)
+            }
+            Error::UnsupportedGraphClass(c) => write!(f, "unsupported graph class '{}'", c),
+            Error::UnsupportedCode(c) => write!(f, "unsupported compression flag '{}'", c),
+            Error::UnexpectedEof => write!(f, "unexpected end of the bit stream"),
+            Error::CodeTooLong => write!(f, "a code in the bit stream doesn't fit in 64 bits"),
+            Error::InvalidSuccessor { node, successor } => {
+                write!(f, "node {} has an invalid successor {}", node, successor)
+            }
+            Error::OutdegreeMismatch { node, outdegree, found } => write!(
+                f,
+                "node {} has outdegree {} but {} successors were decoded",
+                node, outdegree, found
+            ),
+            Error::ArcCountMismatch { expected, found } => {
+                write!(f, "expected {} arcs but decoded {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// The instantaneous codes a `BVGraph` can use for each of its components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    Unary,
+    Gamma,
+    Delta,
+    Zeta(u32),
+}
+
+/// The codes used for each component of a successor list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Codes {
+    pub outdegrees: Code,
+    pub references: Code,
+    pub block_count: Code,
+    pub blocks: Code,
+    pub residuals: Code,
+}
+
+impl Codes {
+    /// The codes WebGraph uses when `compressionflags` is empty.
+    pub fn defaults(zeta_k: u32) -> Self {
+        Self {
+            outdegrees: Code::Gamma,
+            references: Code::Unary,
+            block_count: Code::Gamma,
+            blocks: Code::Gamma,
+            residuals: Code::Zeta(zeta_k),
+        }
+    }
+
+    /// Parses a `compressionflags` value, e.g. `OUTDEGREES_DELTA | RESIDUALS_GAMMA`.
+    fn parse(flags: &str, zeta_k: u32) -> Result<Self, Error> {
+        let mut codes = Self::defaults(zeta_k);
+        for flag in flags.split('|').map(str::trim).filter(|f| !f.is_empty()) {
+            let unsupported = || Error::UnsupportedCode(flag.to_string());
+            let (component, code) = [
+                ("_UNARY", Code::Unary),
+                ("_GAMMA", Code::Gamma),
+                ("_DELTA", Code::Delta),
+                ("_ZETA", Code::Zeta(zeta_k)),
+            ]
+            .iter()
+            .find_map(|(suffix, code)| flag.strip_suffix(suffix).map(|c| (c, *code)))
+            .ok_or_else(unsupported)?;
+            match component {
+                "OUTDEGREES" => codes.outdegrees = code,
+                "REFERENCES" => codes.references = code,
+                "BLOCK_COUNT" => codes.block_count = code,
+                "BLOCKS" => codes.blocks = code,
+                "RESIDUALS" => codes.residuals = code,
+                // offsets are never read since the graph is decoded sequentially
+                "OFFSETS" => (),
+                _ => return Err(unsupported()),
+            }
+        }
+        Ok(codes)
+    }
+}
+
+/// The contents of a `.properties` file that matter for decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Properties {
+    pub nodes: usize,
+    pub arcs: usize,
+    pub window_size: usize,
+    pub min_interval_length: usize,
+    pub zeta_k: u32,
+    pub codes: Codes,
+}
+
+impl Properties {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl FromStr for Properties {
+    type Err = Error;
+
+    /// Parses a java properties file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let map = s
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('!'))
+            .filter_map(|l| {
+                let i = l.find(['=', ':'])?;
+                Some((unescape(l[..i].trim()), unescape(l[i + 1..].trim())))
+            })
+            .collect::<HashMap<_, _>>();
+        if let Some(class) = map.get("graphclass") {
+            if !class.ends_with("BVGraph") {
+                return Err(Error::UnsupportedGraphClass(class.clone()));
+            }
+        }
+        fn get<T: FromStr>(
+            map: &HashMap<String, String>,
+            key: &'static str,
+            default: Option<T>,
+        ) -> Result<T, Error> {
+            match (map.get(key), default) {
+                (Some(v), _) => v.parse().map_err(|_| Error::InvalidProperty {
+                    key,
+                    value: v.clone(),
+                }),
+                (None, Some(d)) => Ok(d),
+                (None, None) => Err(Error::MissingProperty(key)),
+            }
+        }
+        let zeta_k = get(&map, "zetak", Some(3))?;
+        if zeta_k == 0 {
+            return Err(Error::InvalidProperty {
+                key: "zetak",
+                value: zeta_k.to_string(),
+            });
+        }
+        Ok(Self {
+            nodes: get(&map, "nodes", None)?,
+            arcs: get(&map, "arcs", None)?,
+            window_size: get(&map, "windowsize", Some(7))?,
+            min_interval_length: get(&map, "minintervallength", Some(4))?,
+            zeta_k,
+            codes: Codes::parse(
+                map.get("compressionflags").map(|s| &s[..]).unwrap_or(""),
+                zeta_k,
+            )?,
+        })
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Reads bits from a byte slice, most significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    #[inline]
+    fn byte(&self) -> Result<u8, Error> {
+        self.data.get(self.pos / 8).copied().ok_or(Error::UnexpectedEof)
+    }
+
+    fn read_int(&mut self, mut len: u32) -> Result<u64, Error> {
+        if len > 64 {
+            return Err(Error::CodeTooLong);
+        }
+        let mut x = 0u64;
+        while len > 0 {
+            let available = 8 - (self.pos % 8) as u32;
+            let take = u32::min(available, len);
+            let bits = (u32::from(self.byte()?) >> (available - take)) & ((1 << take) - 1);
+            x = (x << take) | u64::from(bits);
+            self.pos += take as usize;
+            len -= take;
+        }
+        Ok(x)
+    }
+
+    fn read_unary(&mut self) -> Result<u64, Error> {
+        let mut count = 0;
+        loop {
+            let offset = self.pos % 8;
+            let rest = self.byte()? << offset;
+            if rest == 0 {
+                count += 8 - offset as u64;
+                self.pos += 8 - offset;
+            } else {
+                let zeros = rest.leading_zeros();
+                self.pos += zeros as usize + 1;
+                return Ok(count + u64::from(zeros));
+            }
+        }
+    }
+
+    /// Reads `msb` bits below an implicit leading one, the tail of gamma and delta codes.
+    fn read_msb(&mut self, msb: u64) -> Result<u64, Error> {
+        if msb >= 64 {
+            return Err(Error::CodeTooLong);
+        }
+        Ok(((1 << msb) | self.read_int(msb as u32)?) - 1)
+    }
+
+    fn read_gamma(&mut self) -> Result<u64, Error> {
+        let msb = self.read_unary()?;
+        self.read_msb(msb)
+    }
+
+    fn read_delta(&mut self) -> Result<u64, Error> {
+        let msb = self.read_gamma()?;
+        self.read_msb(msb)
+    }
+
+    fn read_zeta(&mut self, k: u32) -> Result<u64, Error> {
+        let hk = u32::try_from(self.read_unary()?)
+            .ok()
+            .and_then(|h| h.checked_mul(k))
+            .filter(|&hk| hk < 64)
+            .ok_or(Error::CodeTooLong)?;
+        let left = 1 << hk;
+        let m = self.read_int(hk + k - 1)?;
+        if m < left {
+            Ok(m + left - 1)
+        } else {
+            let m = m.checked_mul(2).ok_or(Error::CodeTooLong)?;
+            Ok(m + self.read_int(1)? - 1)
+        }
+    }
+
+    fn read(&mut self, code: Code) -> Result<u64, Error> {
+        match code {
+            Code::Unary => self.read_unary(),
+            Code::Gamma => self.read_gamma(),
+            Code::Delta => self.read_delta(),
+            Code::Zeta(k) => self.read_zeta(k),
+        }
+    }
+
+    fn read_usize(&mut self, code: Code) -> Result<usize, Error> {
+        self.read(code).map(|x| x as usize)
+    }
+}
+
+/// Maps the natural numbers onto the integers: `0, 1, 2, 3, ...` to `0, -1, 1, -2, ...`.
+#[inline]
+fn nat2int(x: u64) -> i64 {
+    (x >> 1) as i64 ^ -((x & 1) as i64)
+}
+
+/// Loads the graph `basename.graph` described by `basename.properties`, storing its node ids
+/// as `Ix`.
+pub fn load<Ix: VertexIndex, P: AsRef<Path>>(basename: P) -> Result<CSR<(), Ix>, Error> {
+    let basename = basename.as_ref();
+    let properties = Properties::from_path(basename.with_extension("properties"))?;
+    let graph = fs::read(basename.with_extension("graph"))?;
+    decode(&properties, &graph)
+}
+
+/// Loads the strongly connected component of each node from `basename.scc`, as computed by
+/// WebGraph's `StronglyConnectedComponents`.
+pub fn load_scc<P: AsRef<Path>>(basename: P) -> Result<Vec<usize>, Error> {
+    decode_ints(&fs::read(basename.as_ref().with_extension("scc"))?)
+}
+
+/// Loads the size of each strongly connected component from `basename.sccsizes`.
+pub fn load_scc_sizes<P: AsRef<Path>>(basename: P) -> Result<Vec<usize>, Error> {
+    decode_ints(&fs::read(basename.as_ref().with_extension("sccsizes"))?)
+}
+
+/// Decodes the big endian 32 bit integers written by `BinIO.storeInts`.
+fn decode_ints(bytes: &[u8]) -> Result<Vec<usize>, Error> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(Error::UnexpectedEof);
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .collect())
+}
+
+/// `base + gap`, or the error for an out of range successor of `node` if it overflows.
+fn offset(node: usize, base: i64, gap: i128) -> Result<i64, Error> {
+    i64::try_from(i128::from(base) + gap).map_err(|_| Error::InvalidSuccessor {
+        node,
+        successor: if gap < 0 { i64::MIN } else { i64::MAX },
+    })
+}
+
+/// Decodes a `BVGraph` bit stream into a CSR.
+///
+/// # Panics
+/// If the node ids don't fit in `Ix`.
+pub fn decode<Ix: VertexIndex>(
+    properties: &Properties,
+    graph: &[u8],
+) -> Result<CSR<(), Ix>, Error> {
+    let n = properties.nodes;
+    if n > 0 {
+        Ix::new(n - 1);
+    }
+    let codes = properties.codes;
+    let mut bits = BitReader::new(graph);
+    let mut row_indexes = Vec::with_capacity(n + 1);
+    let mut columns: Vec<To<(), Ix>> = Vec::with_capacity(properties.arcs);
+    let mut successors = Vec::new();
+    row_indexes.push(0);
+    for x in 0..n {
+        let degree = bits.read_usize(codes.outdegrees)?;
+        if degree > properties.arcs - columns.len() {
+            return Err(Error::ArcCountMismatch {
+                expected: properties.arcs,
+                found: columns.len().saturating_add(degree),
+            });
+        }
+        successors.clear();
+        if degree > 0 {
+            let reference = if properties.window_size > 0 {
+                bits.read_usize(codes.references)?
+            } else {
+                0
+            };
+            if reference > x || reference > properties.window_size {
+                return Err(Error::InvalidSuccessor {
+                    node: x,
+                    successor: (x as i64).saturating_sub(reference as i64),
+                });
+            }
+            if reference > 0 {
+                let r = x - reference;
+                let referenced = &columns[row_indexes[r]..row_indexes[r + 1]];
+                // Blocks alternate between copying and skipping successors of the referenced
+                // list, starting with copying. An even block count copies whatever is left.
+                let block_count = bits.read_usize(codes.block_count)?;
+                let mut start = 0usize;
+                for i in 0..block_count {
+                    let block = bits
+                        .read_usize(codes.blocks)?
+                        .saturating_add(usize::from(i != 0));
+                    let end = usize::min(start.saturating_add(block), referenced.len());
+                    if i % 2 == 0 {
+                        let copied = referenced[start..end].iter().map(|t| t.to.index() as i64);
+                        successors.extend(copied);
+                    }
+                    start = end;
+                }
+                if block_count % 2 == 0 {
+                    successors.extend(referenced[start..].iter().map(|t| t.to.index() as i64));
+                }
+                if successors.len() > degree {
+                    return Err(Error::OutdegreeMismatch {
+                        node: x,
+                        outdegree: degree,
+                        found: successors.len(),
+                    });
+                }
+            }
+            let mut extra = degree.saturating_sub(successors.len());
+            if extra > 0 && properties.min_interval_length > 0 {
+                let interval_count = bits.read_gamma()?;
+                let mut prev = x as i64;
+                for i in 0..interval_count {
+                    let left = if i == 0 {
+                        offset(x, prev, nat2int(bits.read_gamma()?).into())?
+                    } else {
+                        offset(x, prev, i128::from(bits.read_gamma()?) + 1)?
+                    };
+                    let len = bits
+                        .read_usize(Code::Gamma)?
+                        .saturating_add(properties.min_interval_length);
+                    if len > extra {
+                        return Err(Error::OutdegreeMismatch {
+                            node: x,
+                            outdegree: degree,
+                            found: successors.len().saturating_add(len),
+                        });
+                    }
+                    prev = offset(x, left, len as i128)?;
+                    successors.extend(left..prev);
+                    extra -= len;
+                }
+            }
+            let mut prev = x as i64;
+            for i in 0..extra {
+                prev = if i == 0 {
+                    offset(x, prev, nat2int(bits.read(codes.residuals)?).into())?
+                } else {
+                    offset(x, prev, i128::from(bits.read(codes.residuals)?) + 1)?
+                };
+                successors.push(prev);
+            }
+            if successors.len() != degree {
+                return Err(Error::OutdegreeMismatch {
+                    node: x,
+                    outdegree: degree,
+                    found: successors.len(),
+                });
+            }
+            successors.sort_unstable();
+        }
+        if let Some(&s) = successors.iter().find(|&&s| s < 0 || s >= n as i64) {
+            return Err(Error::InvalidSuccessor {
+                node: x,
+                successor: s,
+            });
+        }
+        columns.extend(successors.iter().map(|&s| To {
+            to: Ix::new(s as usize),
+            weight: (),
+        }));
+        row_indexes.push(columns.len());
+    }
+    if columns.len() != properties.arcs {
+        return Err(Error::ArcCountMismatch {
+            expected: properties.arcs,
+            found: columns.len(),
+        });
+    }
+    Ok(CSR::from_raw_parts(row_indexes.into_boxed_slice(), columns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algorithms::components::strongly_connected,
+        graphs::{FromEdges, Graph, Successors},
+    };
+
+    /// The inverse of `BitReader`, mirroring WebGraph's `OutputBitStream`.
+    #[derive(Default)]
+    struct BitWriter {
+        bytes: Vec<u8>,
+        len: usize,
+    }
+
+    impl BitWriter {
+        fn write_int(&mut self, x: u64, len: u32) {
+            for i in (0..len).rev() {
+                if self.len.is_multiple_of(8) {
+                    self.bytes.push(0);
+                }
+                let bit = ((x >> i) & 1) as u8;
+                *self.bytes.last_mut().unwrap() |= bit << (7 - self.len % 8);
+                self.len += 1;
+            }
+        }
+
+        fn write_unary(&mut self, x: u64) {
+            (0..x).for_each(|_| self.write_int(0, 1));
+            self.write_int(1, 1);
+        }
+
+        fn write_gamma(&mut self, x: u64) {
+            let msb = 63 - (x + 1).leading_zeros();
+            self.write_unary(msb as u64);
+            self.write_int(x + 1, msb);
+        }
+
+        fn write_delta(&mut self, x: u64) {
+            let msb = 63 - (x + 1).leading_zeros();
+            self.write_gamma(msb as u64);
+            self.write_int(x + 1, msb);
+        }
+
+        fn write_zeta(&mut self, x: u64, k: u32) {
+            let x = x + 1;
+            let h = (63 - x.leading_zeros()) / k;
+            self.write_unary(h as u64);
+            let left = 1 << (h * k);
+            if x - left < left {
+                self.write_int(x - left, h * k + k - 1);
+            } else {
+                self.write_int(x, h * k + k);
+            }
+        }
+    }
+
+    fn int2nat(x: i64) -> u64 {
+        ((x << 1) ^ (x >> 63)) as u64
+    }
+
+    #[test]
+    fn codes() {
+        let mut w = BitWriter::default();
+        for i in 0..1000 {
+            w.write_unary(i % 70);
+            w.write_gamma(i);
+            w.write_delta(i * 31);
+            w.write_zeta(i * 7, 3);
+            w.write_zeta(i, 1);
+        }
+        let mut r = BitReader::new(&w.bytes);
+        for i in 0..1000 {
+            assert_eq!(r.read_unary().unwrap(), i % 70);
+            assert_eq!(r.read_gamma().unwrap(), i);
+            assert_eq!(r.read_delta().unwrap(), i * 31);
+            assert_eq!(r.read_zeta(3).unwrap(), i * 7);
+            assert_eq!(r.read_zeta(1).unwrap(), i);
+        }
+    }
+
+    #[test]
+    fn gamma_bits() {
+        // gamma(4) = 00101
+        let mut r = BitReader::new(&[0b0010_1000]);
+        assert_eq!(r.read_gamma().unwrap(), 4);
+        assert_eq!(r.pos, 5);
+    }
+
+    #[test]
+    fn nat_int() {
+        for i in -100..100 {
+            assert_eq!(nat2int(int2nat(i)), i);
+        }
+        assert_eq!(nat2int(0), 0);
+        assert_eq!(nat2int(1), -1);
+        assert_eq!(nat2int(2), 1);
+    }
+
+    #[test]
+    fn properties() {
+        let p: Properties = "#BVGraph properties\n\
+                             #Sat Mar 07 12:00:00 WET 2020\n\
+                             graphclass=it.unimi.dsi.webgraph.BVGraph\n\
+                             nodes=325557\n\
+                             arcs=3216152\n\
+                             windowsize=7\n\
+                             minintervallength=4\n\
+                             zetak=5\n\
+                             compressionflags=OUTDEGREES_DELTA | BLOCK_COUNT_UNARY\n"
+            .parse()
+            .unwrap();
+        assert_eq!(p.nodes, 325_557);
+        assert_eq!(p.arcs, 3_216_152);
+        assert_eq!(
+            p.codes,
+            Codes {
+                outdegrees: Code::Delta,
+                block_count: Code::Unary,
+                ..Codes::defaults(5)
+            }
+        );
+        assert!(matches!(
+            "nodes=1\n".parse::<Properties>(),
+            Err(Error::MissingProperty("arcs"))
+        ));
+        assert!(matches!(
+            "nodes=1\narcs=1\ncompressionflags=RESIDUALS_NIBBLE".parse::<Properties>(),
+            Err(Error::UnsupportedCode(_))
+        ));
+    }
+
+    #[test]
+    fn decode_graph() {
+        let properties = Properties {
+            nodes: 10,
+            arcs: 16,
+            window_size: 7,
+            min_interval_length: 4,
+            zeta_k: 3,
+            codes: Codes::defaults(3),
+        };
+        let mut w = BitWriter::default();
+        // 0 -> [1, 2, 3, 4, 5, 9]: one interval and one residual
+        w.write_gamma(6);
+        w.write_unary(0);
+        w.write_gamma(1);
+        w.write_gamma(int2nat(1));
+        w.write_gamma(5 - 4);
+        w.write_zeta(int2nat(9), 3);
+        // 1 -> [0, 2, 3, 4, 5, 9]: copies all but the first successor of 0
+        w.write_gamma(6);
+        w.write_unary(1);
+        w.write_gamma(2);
+        w.write_gamma(0);
+        w.write_gamma(1 - 1);
+        w.write_gamma(0);
+        w.write_zeta(int2nat(-1), 3);
+        // 2 -> []
+        w.write_gamma(0);
+        // 3 -> [0, 2, 7]: copies the first two successors of 1
+        w.write_gamma(3);
+        w.write_unary(2);
+        w.write_gamma(1);
+        w.write_gamma(2);
+        w.write_gamma(0);
+        w.write_zeta(int2nat(7 - 3), 3);
+        // 4 -> [4]
+        w.write_gamma(1);
+        w.write_unary(0);
+        w.write_gamma(0);
+        w.write_zeta(int2nat(0), 3);
+        (5..10).for_each(|_| w.write_gamma(0));
+
+        let g: CSR = decode(&properties, &w.bytes).unwrap();
+        assert_eq!(g.vertices(), 10);
+        assert_eq!(g.edges(), 16);
+        let succ = |v| g[v].iter().map(|t| t.to).collect::<Vec<_>>();
+        assert_eq!(succ(0), [1, 2, 3, 4, 5, 9]);
+        assert_eq!(succ(1), [0, 2, 3, 4, 5, 9]);
+        assert_eq!(succ(2), []);
+        assert_eq!(succ(3), [0, 2, 7]);
+        assert_eq!(succ(4), [4]);
+        (5..10).for_each(|v| assert!(succ(v).is_empty()));
+        let narrow: CSR<(), u32> = decode(&properties, &w.bytes).unwrap();
+        assert!((0..10).all(|v| narrow.successors(v).eq(g.successors(v))));
+
+        assert!(matches!(
+            decode::<usize>(&Properties { arcs: 17, ..properties.clone() }, &w.bytes),
+            Err(Error::ArcCountMismatch { expected: 17, found: 16 })
+        ));
+        assert!(matches!(
+            decode::<usize>(&Properties { nodes: 11, ..properties.clone() }, &w.bytes),
+            Err(Error::UnexpectedEof)
+        ));
+
+        // 1 copies all 6 successors of 0 while claiming to only have 2
+        let mut w = BitWriter::default();
+        w.write_gamma(6);
+        w.write_unary(0);
+        w.write_gamma(1);
+        w.write_gamma(int2nat(1));
+        w.write_gamma(5 - 4);
+        w.write_zeta(int2nat(9), 3);
+        w.write_gamma(2);
+        w.write_unary(1);
+        w.write_gamma(0);
+        (2..10).for_each(|_| w.write_gamma(0));
+        assert!(matches!(
+            decode::<usize>(&Properties { arcs: 8, ..properties }, &w.bytes),
+            Err(Error::OutdegreeMismatch { node: 1, outdegree: 2, found: 6 })
+        ));
+    }
+
+    #[test]
+    fn malformed_codes() {
+        let mut w = BitWriter::default();
+        w.write_unary(64);
+        w.write_int(0, 64);
+        assert!(matches!(
+            BitReader::new(&w.bytes).read_gamma(),
+            Err(Error::CodeTooLong)
+        ));
+        assert!(matches!(
+            BitReader::new(&w.bytes).read_zeta(3),
+            Err(Error::CodeTooLong)
+        ));
+        assert!(matches!(
+            BitReader::new(&w.bytes).read_int(65),
+            Err(Error::CodeTooLong)
+        ));
+        let mut w = BitWriter::default();
+        w.write_gamma(64);
+        assert!(matches!(
+            BitReader::new(&w.bytes).read_delta(),
+            Err(Error::CodeTooLong)
+        ));
+        assert!(matches!(
+            "nodes=1\narcs=0\nzetak=0".parse::<Properties>(),
+            Err(Error::InvalidProperty { key: "zetak", .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_graphs() {
+        let properties = Properties {
+            nodes: 2,
+            arcs: 2,
+            window_size: 7,
+            min_interval_length: 4,
+            zeta_k: 3,
+            codes: Codes {
+                residuals: Code::Gamma,
+                ..Codes::defaults(3)
+            },
+        };
+        let decode = |w: &BitWriter| decode::<usize>(&properties, &w.bytes);
+
+        // 0 -> [1] and 1 copies it with a block longer than any successor list
+        let mut w = BitWriter::default();
+        w.write_gamma(1);
+        w.write_unary(0);
+        w.write_gamma(0);
+        w.write_gamma(int2nat(1));
+        w.write_gamma(1);
+        w.write_unary(1);
+        w.write_gamma(1);
+        w.write_gamma(u64::MAX - 1);
+        let g = decode(&w).unwrap();
+        assert!((0..2).all(|v| g.successors(v).eq(Some(1))));
+
+        // a residual gap past i64::MAX
+        let mut w = BitWriter::default();
+        w.write_gamma(2);
+        w.write_unary(0);
+        w.write_gamma(0);
+        w.write_gamma(int2nat(1));
+        w.write_gamma(u64::MAX - 1);
+        assert!(matches!(
+            decode(&w),
+            Err(Error::InvalidSuccessor { node: 0, successor: i64::MAX })
+        ));
+
+        // an interval longer than the outdegree
+        let mut w = BitWriter::default();
+        w.write_gamma(2);
+        w.write_unary(0);
+        w.write_gamma(1);
+        w.write_gamma(int2nat(0));
+        w.write_gamma(u64::MAX - 1);
+        assert!(matches!(
+            decode(&w),
+            Err(Error::OutdegreeMismatch { node: 0, outdegree: 2, .. })
+        ));
+
+        // more successors than arcs
+        let mut w = BitWriter::default();
+        w.write_gamma(u64::MAX - 1);
+        assert!(matches!(
+            decode(&w),
+            Err(Error::ArcCountMismatch { expected: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn scc_files() {
+        // 0 <-> 1, 2 -> 0 and 3 on its own, numbered the way WebGraph numbers them
+        let scc = [1u32, 1, 0, 2];
+        let bytes = scc.iter().flat_map(|c| c.to_be_bytes()).collect::<Vec<_>>();
+        assert_eq!(&bytes[..8], [0, 0, 0, 1, 0, 0, 0, 1]);
+        let scc = decode_ints(&bytes).unwrap();
+        assert_eq!(scc, [1, 1, 0, 2]);
+        assert!(matches!(decode_ints(&bytes[1..]), Err(Error::UnexpectedEof)));
+
+        let g = <CSR as FromEdges>::from_edges(4, vec![(0, 1), (1, 0), (2, 0)]);
+        let components = strongly_connected(&g);
+        assert!(components.same_partition(&scc));
+        assert_eq!(components.sizes().iter().sum::<usize>(), 4);
+    }
+}
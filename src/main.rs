@@ -1,3 +1,1012 @@
+use aava::{
+    algorithms::{
+        apl::{
+            self,
+            hyper_ball::{hyper_ball, par_hyper_ball},
+            AplReport, AplValue, Unreachable, UnreachablePairs,
+        },
+        clustering_coef::{
+            c_coef,
+            directed::{Directed, Motif},
+            exact::triangles,
+            SampleWedges,
+        },
+        components::{self, Components},
+        min_cut,
+    },
+    graphs::{
+        compressed::CompressedCSR,
+        csr::CSR,
+        edge_list::EdgeList,
+        io::{self, EdgeSource},
+        mapped::{self, MappedCSR},
+        matrix::Adjacency,
+        sorted::Sorted,
+        test_graphs::{clustered, random_graph, random_graph_er},
+        transform::Transform,
+        webgraph, EdgeListGraph, FromEdges, Graph, Successors, VertexIndex,
+    },
+    util::{
+        disjoint_set::{
+            DisjointSet, FindMode, PathCompression, PathHalving, PathSplitting, SimpleDisjointSet,
+        },
+        hyper_counters::{CompactHyperLogLogArray, HyperLogLog, B},
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs::File,
+    io::{stdout, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
+use structopt::StructOpt;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "aava",
+    about = "Run graph algorithms on generated or stored graphs"
+)]
+struct Opt {
+    /// How to print results: text, csv or json
+    #[structopt(short, long, default_value = "text")]
+    output: OutputFormat,
+    /// Seed for the graph generators and randomized algorithms
+    #[structopt(short, long)]
+    seed: Option<u64>,
+    /// How many times to run the algorithm
+    #[structopt(short, long, default_value = "1")]
+    repetitions: usize,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Exact or sampled average path length
+    Apl {
+        #[structopt(flatten)]
+        input: Input,
+        /// Algorithm: floyd (Floyd-Warshall over an edge list), bfs (BFS from every vertex) or
+        /// sampled (BFS from random vertices). Every method takes the edges as undirected
+        #[structopt(short, long, default_value = "floyd")]
+        method: AplMethod,
+        #[structopt(flatten)]
+        unreachable: UnreachableOpt,
+        #[structopt(flatten)]
+        sampling: Sampling,
+    },
+    /// Approximate average path length using HyperBall
+    Hyperball {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        counters: Counters,
+        #[structopt(flatten)]
+        unreachable: UnreachableOpt,
+    },
+    /// Approximate or exact clustering coefficient
+    Cc {
+        #[structopt(flatten)]
+        input: Input,
+        /// Number of sampled wedges
+        #[structopt(short, long, default_value = "1000")]
+        k: usize,
+        /// Count every triangle instead of sampling
+        #[structopt(long)]
+        exact: bool,
+        /// Directed triangle type: cycle, middleman, in or out
+        #[structopt(long)]
+        motif: Option<MotifKind>,
+    },
+    /// Minimum cut using Karger-Stein
+    Mincut {
+        #[structopt(flatten)]
+        input: Input,
+        /// Union find path strategy: compression, halving or splitting
+        #[structopt(long, default_value = "compression")]
+        find_mode: FindModeKind,
+        /// Use the undo disjoint set instead of cloning it
+        #[structopt(long)]
+        fast: bool,
+    },
+    /// Weakly or strongly connected components and how many there are of each size
+    Components {
+        #[structopt(flatten)]
+        input: Input,
+        /// Strongly connected components instead of weakly connected ones
+        #[structopt(long)]
+        strong: bool,
+        /// Compare the strongly connected components with WebGraph's `.scc` and `.sccsizes`
+        /// files of this basename
+        #[structopt(long, requires = "strong")]
+        check: Option<PathBuf>,
+    },
+    /// Generate a graph and write it out
+    Generate {
+        /// Generator spec: `er:N,P`, `gnm:N,M` or `clustered:N,D,O`
+        generator: Generator,
+        #[structopt(flatten)]
+        output: GraphOutput,
+    },
+    /// Convert a graph between file formats
+    Convert {
+        #[structopt(flatten)]
+        input: Input,
+        #[structopt(flatten)]
+        output: GraphOutput,
+    },
+}
+
+#[derive(StructOpt)]
+struct Input {
+    /// A graph file or a generator spec (`er:N,P`, `gnm:N,M` or `clustered:N,D,O`)
+    graph: Source,
+    /// Format of the graph file: edges, mm, webgraph or binary. Guessed from the extension if
+    /// omitted
+    #[structopt(short, long)]
+    format: Option<FileFormat>,
+    /// Graph representation: csr, adjacency, edge-list, compressed or mapped (a binary file
+    /// used in place)
+    #[structopt(long)]
+    repr: Option<Repr>,
+    /// Integer type the csr representation stores vertex ids as: usize or u32, which halves the
+    /// memory taken by the arcs of graphs with less than 2^32 vertices
+    #[structopt(long, default_value = "usize")]
+    ids: Ids,
+}
+
+#[derive(StructOpt)]
+struct Counters {
+    /// Counter type: hyperloglog or compact
+    #[structopt(long, default_value = "hyperloglog")]
+    counter: CounterKind,
+    /// Number of bits used to index the registers (4 to 15)
+    #[structopt(short, long, default_value = "4")]
+    b: u8,
+    /// Split each round among all cores
+    #[structopt(long)]
+    parallel: bool,
+}
+
+#[derive(StructOpt)]
+struct Sampling {
+    /// Number of sampled sources, ignored if --relative-error is given
+    #[structopt(long, default_value = "100")]
+    samples: usize,
+    /// Sample until the confidence interval is within this fraction of the estimate
+    #[structopt(long)]
+    relative_error: Option<f64>,
+    /// Confidence level of the interval
+    #[structopt(long, default_value = "0.95")]
+    confidence: f64,
+}
+
+#[derive(StructOpt)]
+struct UnreachableOpt {
+    /// What to do with unconnected pairs: skip, harmonic, per-component or error
+    #[structopt(long, default_value = "skip")]
+    unreachable: UnreachablePolicy,
+}
+
+#[derive(StructOpt)]
+struct GraphOutput {
+    /// Format to write: edges, mm or binary
+    #[structopt(short, long, default_value = "edges")]
+    to: FileFormat,
+    /// File to write to, stdout if omitted
+    #[structopt(long = "out")]
+    out: Option<PathBuf>,
+}
+
+macro_rules! from_str_enum {
+    ($t:ident { $($s:expr => $v:ident),* $(,)? }) => {
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        enum $t { $($v),* }
+
+        impl FromStr for $t {
+            type Err = String;
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                match s {
+                    $($s => Ok($t::$v),)*
+                    _ => Err(format!(
+                        "invalid value '{}', expected one of: {}",
+                        s,
+                        [$($s),*].join(", ")
+                    )),
+                }
+            }
+        }
+
+        impl Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $($t::$v => f.write_str($s),)*
+                }
+            }
+        }
+    };
+}
+
+from_str_enum!(OutputFormat { "text" => Text, "csv" => Csv, "json" => Json });
+from_str_enum!(FileFormat {
+    "edges" => Edges,
+    "mm" => MatrixMarket,
+    "webgraph" => WebGraph,
+    "binary" => Binary,
+});
+from_str_enum!(Repr {
+    "csr" => Csr,
+    "adjacency" => Adjacency,
+    "edge-list" => EdgeList,
+    "compressed" => Compressed,
+    "mapped" => Mapped,
+});
+from_str_enum!(Ids { "usize" => Usize, "u32" => U32 });
+from_str_enum!(AplMethod { "floyd" => Floyd, "bfs" => Bfs, "sampled" => Sampled });
+from_str_enum!(UnreachablePolicy {
+    "skip" => Skip,
+    "harmonic" => Harmonic,
+    "per-component" => PerComponent,
+    "error" => Error,
+});
+from_str_enum!(MotifKind {
+    "cycle" => Cycle,
+    "middleman" => Middleman,
+    "in" => In,
+    "out" => Out,
+});
+from_str_enum!(CounterKind { "hyperloglog" => HyperLogLog, "compact" => Compact });
+from_str_enum!(FindModeKind {
+    "compression" => Compression,
+    "halving" => Halving,
+    "splitting" => Splitting,
+});
+
+enum Generator {
+    Er { n: usize, p: f64 },
+    Gnm { n: usize, m: usize },
+    Clustered { n: usize, d: usize, o: usize },
+}
+
+impl FromStr for Generator {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid generator spec '{}'", s);
+        let (kind, args) = s.split_at(s.find(':').ok_or_else(invalid)?);
+        let args = args[1..].split(',').collect::<Vec<_>>();
+        fn arg<T: FromStr>(s: &str) -> std::result::Result<T, String> {
+            s.trim()
+                .parse()
+                .map_err(|_| format!("invalid generator argument '{}'", s))
+        }
+        match (kind, &args[..]) {
+            ("er", [n, p]) => Ok(Generator::Er {
+                n: arg(n)?,
+                p: arg(p)?,
+            }),
+            ("gnm", [n, m]) => Ok(Generator::Gnm {
+                n: arg(n)?,
+                m: arg(m)?,
+            }),
+            ("clustered", [n, d, o]) => Ok(Generator::Clustered {
+                n: arg(n)?,
+                d: arg(d)?,
+                o: arg(o)?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Generator {
+    fn generate<R: Rng>(&self, rng: R) -> (usize, Vec<(usize, usize)>) {
+        match *self {
+            Generator::Er { n, p } => (n, random_graph_er(n, p, rng)),
+            Generator::Gnm { n, m } => (n, random_graph(n, m, rng)),
+            Generator::Clustered { n, d, o } => {
+                let g = clustered::<Adjacency, _>(n, d, o, rng);
+                (n, edges(&g))
+            }
+        }
+    }
+}
+
+enum Source {
+    Generator(Generator),
+    File(PathBuf),
+}
+
+impl FromStr for Source {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if ["er:", "gnm:", "clustered:"]
+            .iter()
+            .any(|p| s.starts_with(p))
+        {
+            s.parse().map(Source::Generator)
+        } else {
+            Ok(Source::File(s.into()))
+        }
+    }
+}
+
+fn edges<G: EdgeSource<EdgeWeight = ()>>(g: &G) -> Vec<(usize, usize)> {
+    let mut edges = Vec::with_capacity(g.edges());
+    g.try_for_each_edge::<_, ()>(|from, to, _| {
+        edges.push((from, to));
+        Ok(())
+    })
+    .unwrap();
+    edges
+}
+
+fn guess_format(path: &Path) -> FileFormat {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("mtx") | Some("mm") => FileFormat::MatrixMarket,
+        Some("graph") | Some("properties") => FileFormat::WebGraph,
+        Some("csr") => FileFormat::Binary,
+        _ => FileFormat::Edges,
+    }
+}
+
+/// The representations a graph can be loaded as.
+trait Load: FromEdges {
+    /// The vertex ids of the CSR graph files are decoded into.
+    type Ix: VertexIndex;
+
+    /// Converts a graph file that already decoded to a CSR, without going through its edges.
+    fn from_csr(g: CSR<(), Self::Ix>) -> Self;
+}
+
+impl<Ix: VertexIndex> Load for CSR<(), Ix> {
+    type Ix = Ix;
+
+    fn from_csr(g: CSR<(), Ix>) -> Self {
+        g
+    }
+}
+
+impl Load for Adjacency {
+    type Ix = usize;
+
+    fn from_csr(g: CSR) -> Self {
+        Adjacency::from(g)
+    }
+}
+
+impl Load for EdgeList {
+    type Ix = usize;
+
+    fn from_csr(g: CSR) -> Self {
+        EdgeList::from(g)
+    }
+}
+
+impl Load for CompressedCSR {
+    type Ix = usize;
+
+    fn from_csr(g: CSR) -> Self {
+        CompressedCSR::from_csr(&g)
+    }
+}
+
+impl Input {
+    fn load<G: Load, R: Rng>(&self, rng: R) -> Result<G> {
+        let (n, edges) = match &self.graph {
+            Source::Generator(g) => g.generate(rng),
+            Source::File(path) => {
+                let reader = || File::open(path).map(BufReader::new);
+                match self.format.unwrap_or_else(|| guess_format(path)) {
+                    FileFormat::Edges => {
+                        let g: EdgeList = io::read_edge_list(reader()?)?;
+                        (
+                            g.vertices(),
+                            g.into_edges().into_iter().map(|e| (e.0, e.1)).collect(),
+                        )
+                    }
+                    FileFormat::MatrixMarket => {
+                        let g: EdgeList = io::read_matrix_market(reader()?)?;
+                        (
+                            g.vertices(),
+                            g.into_edges().into_iter().map(|e| (e.0, e.1)).collect(),
+                        )
+                    }
+                    FileFormat::WebGraph => return Ok(G::from_csr(webgraph::load(path)?)),
+                    FileFormat::Binary => {
+                        let g: MappedCSR = MappedCSR::open(path)?;
+                        return Ok(G::from_csr(g.to_csr()));
+                    }
+                }
+            }
+        };
+        Ok(G::from_edges(n, edges))
+    }
+
+    /// Loads the graph with both directions of every arc, so the BFS based methods find the
+    /// same distances as Floyd-Warshall.
+    fn load_symmetric<G: Load + Transform, R: Rng>(&self, rng: R) -> Result<G> {
+        Ok(self.load::<G, R>(rng)?.symmetrize(|a, _| a))
+    }
+
+    /// Maps a binary graph file instead of loading it.
+    fn map(&self) -> Result<MappedCSR> {
+        match &self.graph {
+            Source::File(path)
+                if self.format.unwrap_or_else(|| guess_format(path)) == FileFormat::Binary =>
+            {
+                Ok(MappedCSR::open(path)?)
+            }
+            _ => Err("the mapped representation needs a binary graph file".into()),
+        }
+    }
+
+    fn repr(&self, supported: &[Repr]) -> Result<Repr> {
+        let repr = match self.repr {
+            None => supported[0],
+            Some(r) if supported.contains(&r) => r,
+            Some(r) => {
+                return Err(format!(
+                    "representation '{}' not supported, expected one of: {}",
+                    r,
+                    supported
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+                .into())
+            }
+        };
+        if self.ids != Ids::Usize && repr != Repr::Csr {
+            return Err(format!("--ids {} needs the csr representation", self.ids).into());
+        }
+        Ok(repr)
+    }
+}
+
+/// Loads `$input` as a CSR with the vertex ids it asks for, and evaluates `$body` with the
+/// graph bound to `$g` for each of them.
+macro_rules! with_csr {
+    ($input:expr, $rng:expr, |$g:ident| $body:expr) => {
+        match $input.ids {
+            Ids::Usize => {
+                let $g: CSR = $input.load($rng)?;
+                $body
+            }
+            Ids::U32 => {
+                let $g: CSR<(), u32> = $input.load($rng)?;
+                $body
+            }
+        }
+    };
+}
+
+impl GraphOutput {
+    fn write<G>(&self, g: &G) -> Result<()>
+    where
+        G: EdgeSource<EdgeWeight = ()>,
+    {
+        let out: Box<dyn Write> = match &self.out {
+            Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+            None => Box::new(BufWriter::new(stdout())),
+        };
+        match self.to {
+            FileFormat::Edges => io::write_edge_list(g, out)?,
+            FileFormat::MatrixMarket => io::write_matrix_market(g, out)?,
+            FileFormat::WebGraph => return Err("writing webgraph files is not supported".into()),
+            FileFormat::Binary => {
+                mapped::write(&<CSR as FromEdges>::from_edges(g.vertices(), edges(g)), out)?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<UnreachablePolicy> for Unreachable {
+    fn from(p: UnreachablePolicy) -> Self {
+        match p {
+            UnreachablePolicy::Skip => Unreachable::Skip,
+            UnreachablePolicy::Harmonic => Unreachable::Harmonic,
+            UnreachablePolicy::PerComponent => Unreachable::PerComponent,
+            UnreachablePolicy::Error => Unreachable::Error,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Value {
+    Float(f64),
+    Int(usize),
+    /// `(component, apl)` pairs
+    Components(Vec<(usize, f64)>),
+    /// `(size, count)` pairs
+    Histogram(Vec<(usize, usize)>),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Int(x) => write!(f, "{}", x),
+            Value::Components(c) => {
+                let c = c
+                    .iter()
+                    .map(|(c, apl)| format!("{}:{}", c, apl))
+                    .collect::<Vec<_>>();
+                f.write_str(&c.join(" "))
+            }
+            Value::Histogram(h) => {
+                let h = h
+                    .iter()
+                    .map(|(size, count)| format!("{}:{}", size, count))
+                    .collect::<Vec<_>>();
+                f.write_str(&h.join(" "))
+            }
+        }
+    }
+}
+
+impl Value {
+    fn to_json(&self) -> String {
+        let float = |x: f64| {
+            if x.is_finite() {
+                x.to_string()
+            } else {
+                "null".to_string()
+            }
+        };
+        match self {
+            Value::Float(x) => float(*x),
+            Value::Int(x) => x.to_string(),
+            Value::Components(c) => {
+                let c = c
+                    .iter()
+                    .map(|(c, apl)| format!(r#"{{"component":{},"apl":{}}}"#, c, float(*apl)))
+                    .collect::<Vec<_>>();
+                format!("[{}]", c.join(","))
+            }
+            Value::Histogram(h) => {
+                let h = h
+                    .iter()
+                    .map(|(size, count)| format!(r#"{{"size":{},"count":{}}}"#, size, count))
+                    .collect::<Vec<_>>();
+                format!("[{}]", h.join(","))
+            }
+        }
+    }
+}
+
+struct Record {
+    command: &'static str,
+    repetition: usize,
+    value: Value,
+    millis: f64,
+}
+
+fn print_records(format: OutputFormat, records: &[Record]) {
+    match format {
+        OutputFormat::Text => records.iter().for_each(|r| {
+            println!(
+                "{} #{}: {} ({:.3} ms)",
+                r.command, r.repetition, r.value, r.millis
+            )
+        }),
+        OutputFormat::Csv => {
+            println!("command,repetition,value,millis");
+            records
+                .iter()
+                .for_each(|r| println!("{},{},{},{}", r.command, r.repetition, r.value, r.millis))
+        }
+        OutputFormat::Json => {
+            let json = records
+                .iter()
+                .map(|r| {
+                    format!(
+                        r#"{{"command":"{}","repetition":{},"value":{},"millis":{}}}"#,
+                        r.command,
+                        r.repetition,
+                        r.value.to_json(),
+                        r.millis
+                    )
+                })
+                .collect::<Vec<_>>();
+            println!("[{}]", json.join(","))
+        }
+    }
+}
+
+fn repeat<F>(command: &'static str, repetitions: usize, mut f: F) -> Vec<Record>
+where
+    F: FnMut() -> Value,
+{
+    (0..repetitions)
+        .map(|repetition| {
+            let start = Instant::now();
+            let value = f();
+            Record {
+                command,
+                repetition,
+                value,
+                millis: start.elapsed().as_secs_f64() * 1000.0,
+            }
+        })
+        .collect()
+}
+
+/// Like [`repeat`] but each run may fail and produce several values, all timed together.
+fn repeat_many<F>(repetitions: usize, mut f: F) -> Result<Vec<Record>>
+where
+    F: FnMut() -> Result<Vec<(&'static str, Value)>>,
+{
+    let mut records = Vec::new();
+    for repetition in 0..repetitions {
+        let start = Instant::now();
+        let values = f()?;
+        let millis = start.elapsed().as_secs_f64() * 1000.0;
+        records.extend(values.into_iter().map(|(command, value)| Record {
+            command,
+            repetition,
+            value,
+            millis,
+        }));
+    }
+    Ok(records)
+}
+
+/// Adds an `unreachable` record with the number of unconnected pairs after each value.
+fn repeat_apl<F>(command: &'static str, repetitions: usize, mut f: F) -> Result<Vec<Record>>
+where
+    F: FnMut() -> std::result::Result<AplReport, UnreachablePairs>,
+{
+    repeat_many(repetitions, || {
+        let report = f()?;
+        let value = match report.value {
+            AplValue::Mean(x) | AplValue::Harmonic(x) => Value::Float(x),
+            AplValue::PerComponent(c) => {
+                Value::Components(c.iter().map(|c| (c.component, c.apl)).collect())
+            }
+        };
+        Ok(vec![
+            (command, value),
+            ("unreachable", Value::Int(report.unreachable_pairs)),
+        ])
+    })
+}
+
+fn hyperball<G: Successors + Sync, R: Rng>(
+    g: &G,
+    counters: &Counters,
+    policy: Unreachable,
+    repetitions: usize,
+    mut rng: R,
+) -> Result<Vec<Record>> {
+    let b = b_from(counters.b)?;
+    let n = g.vertices();
+    repeat_apl("hyperball", repetitions, || {
+        let seed = rng.gen();
+        let result = match (counters.counter, counters.parallel) {
+            (CounterKind::HyperLogLog, parallel) => {
+                let c = vec![HyperLogLog::new_with_seed(b, seed); n].into_boxed_slice();
+                if parallel {
+                    par_hyper_ball(g, c)
+                } else {
+                    hyper_ball(g, c)
+                }
+            }
+            (CounterKind::Compact, parallel) => {
+                let c = CompactHyperLogLogArray::new_with_seed(b, n, seed);
+                if parallel {
+                    par_hyper_ball(g, c)
+                } else {
+                    hyper_ball(g, c)
+                }
+            }
+        };
+        result.apl_with(g, policy)
+    })
+}
+
+fn sampled<G: Successors, R: Rng>(
+    g: &G,
+    sampling: &Sampling,
+    repetitions: usize,
+    mut rng: R,
+) -> Result<Vec<Record>> {
+    repeat_many(repetitions, || {
+        let e = match sampling.relative_error {
+            Some(error) => apl::sampled::apl_until(g, error, sampling.confidence, &mut rng),
+            None => apl::sampled::apl(g, sampling.samples, sampling.confidence, &mut rng),
+        };
+        Ok(vec![
+            ("apl", Value::Float(e.apl)),
+            ("standard-error", Value::Float(e.standard_error)),
+            ("samples", Value::Int(e.samples)),
+        ])
+    })
+}
+
+fn b_from(b: u8) -> Result<B> {
+    Ok(match b {
+        4 => B::B4,
+        5 => B::B5,
+        6 => B::B6,
+        7 => B::B7,
+        8 => B::B8,
+        9 => B::B9,
+        10 => B::B10,
+        11 => B::B11,
+        12 => B::B12,
+        13 => B::B13,
+        14 => B::B14,
+        15 => B::B15,
+        _ => return Err(format!("b must be between 4 and 15, got {}", b).into()),
+    })
+}
+
+fn clustering<G: SampleWedges, R: Rng>(
+    g: &G,
+    k: usize,
+    repetitions: usize,
+    mut rng: R,
+) -> Result<Vec<Record>> {
+    repeat_many(repetitions, || {
+        let c = c_coef(k, g, &mut rng)?;
+        Ok(vec![
+            ("cc", Value::Float(c.value)),
+            ("excluded", Value::Int(c.excluded)),
+        ])
+    })
+}
+
+/// Checks `c` against the strongly connected components WebGraph computed for `basename`.
+fn check_scc(c: &Components, basename: &Path) -> Result<()> {
+    if !c.same_partition(&webgraph::load_scc(basename)?) {
+        return Err("the strongly connected components differ from the .scc file".into());
+    }
+    let mut sizes = c.sizes();
+    let mut expected = webgraph::load_scc_sizes(basename)?;
+    sizes.sort_unstable();
+    expected.sort_unstable();
+    if sizes != expected {
+        return Err("the component sizes differ from the .sccsizes file".into());
+    }
+    Ok(())
+}
+
+fn mincut<F: FindMode>(g: &EdgeList, fast: bool, repetitions: usize) -> Vec<Record>
+where
+    SimpleDisjointSet<F>: DisjointSet + Clone,
+{
+    repeat("mincut", repetitions, || {
+        let mut g: EdgeList = EdgeList::from_edges(
+            g.vertices(),
+            g.as_edges().iter().map(|e| (e.0, e.1)).collect::<Vec<_>>(),
+        );
+        let cut = if fast {
+            min_cut::fast_karger_stein::<_, F>(&mut g)
+        } else {
+            min_cut::karger_stein::<_, F>(&mut g)
+        };
+        Value::Int(cut.len())
+    })
+}
+
+fn run(opt: Opt) -> Result<()> {
+    let mut rng = match opt.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let reps = opt.repetitions;
+    let records = match &opt.command {
+        Command::Apl {
+            input,
+            method,
+            unreachable,
+            sampling,
+        } => {
+            let policy = unreachable.unreachable.into();
+            match method {
+                AplMethod::Floyd => {
+                    match input.repr(&[Repr::EdgeList, Repr::Csr, Repr::Adjacency])? {
+                        Repr::Csr => with_csr!(input, &mut rng, |g| {
+                            repeat_apl("apl", reps, || apl::apl_with(&g, policy))?
+                        }),
+                        Repr::Adjacency => {
+                            let g: Adjacency = input.load(&mut rng)?;
+                            repeat_apl("apl", reps, || apl::apl_with(&g, policy))?
+                        }
+                        _ => {
+                            let g: EdgeList = input.load(&mut rng)?;
+                            repeat_apl("apl", reps, || apl::apl_with(&g, policy))?
+                        }
+                    }
+                }
+                AplMethod::Sampled => {
+                    match input.repr(&[Repr::Csr, Repr::Adjacency, Repr::Compressed])? {
+                        Repr::Adjacency => {
+                            let g: Adjacency = input.load_symmetric(&mut rng)?;
+                            sampled(&g, sampling, reps, &mut rng)?
+                        }
+                        Repr::Compressed => {
+                            let g =
+                                CompressedCSR::from_csr(&input.load_symmetric::<CSR, _>(&mut rng)?);
+                            sampled(&g, sampling, reps, &mut rng)?
+                        }
+                        _ => with_csr!(input, &mut rng, |g| {
+                            let g = g.symmetrize(|a, _| a);
+                            sampled(&g, sampling, reps, &mut rng)?
+                        }),
+                    }
+                }
+                AplMethod::Bfs => {
+                    match input.repr(&[Repr::Csr, Repr::Adjacency, Repr::Compressed])? {
+                        Repr::Adjacency => {
+                            let g: Adjacency = input.load_symmetric(&mut rng)?;
+                            repeat_apl("apl", reps, || apl::bfs::apl_with(&g, policy))?
+                        }
+                        Repr::Compressed => {
+                            let g =
+                                CompressedCSR::from_csr(&input.load_symmetric::<CSR, _>(&mut rng)?);
+                            repeat_apl("apl", reps, || apl::bfs::apl_with(&g, policy))?
+                        }
+                        _ => with_csr!(input, &mut rng, |g| {
+                            let g = g.symmetrize(|a, _| a);
+                            repeat_apl("apl", reps, || apl::bfs::apl_with(&g, policy))?
+                        }),
+                    }
+                }
+            }
+        }
+        Command::Hyperball {
+            input,
+            counters,
+            unreachable,
+        } => {
+            let policy = unreachable.unreachable.into();
+            match input.repr(&[Repr::Csr, Repr::Adjacency, Repr::Compressed, Repr::Mapped])? {
+                Repr::Mapped => {
+                    let g = input.map()?;
+                    hyperball(&g, counters, policy, reps, &mut rng)?
+                }
+                Repr::Adjacency => {
+                    let g: Adjacency = input.load(&mut rng)?;
+                    hyperball(&g, counters, policy, reps, &mut rng)?
+                }
+                Repr::Compressed => {
+                    let g: CompressedCSR = input.load(&mut rng)?;
+                    hyperball(&g, counters, policy, reps, &mut rng)?
+                }
+                _ => with_csr!(input, &mut rng, |g| {
+                    hyperball(&g, counters, policy, reps, &mut rng)?
+                }),
+            }
+        }
+        Command::Cc {
+            input,
+            k,
+            exact,
+            motif: Some(motif),
+        } => {
+            input.repr(&[Repr::Csr])?;
+            let motif = match motif {
+                MotifKind::Cycle => Motif::Cycle,
+                MotifKind::Middleman => Motif::Middleman,
+                MotifKind::In => Motif::In,
+                MotifKind::Out => Motif::Out,
+            };
+            with_csr!(input, &mut rng, |g| {
+                let g = Sorted::new(g);
+                repeat_many(reps, || {
+                    let d = Directed::new(&g);
+                    Ok(if *exact {
+                        vec![("average-local", Value::Float(d.average_local(motif)))]
+                    } else {
+                        let e = d.sample(motif, *k, 0.95, &mut rng);
+                        vec![
+                            ("cc", Value::Float(e.value)),
+                            ("error", Value::Float(e.error)),
+                        ]
+                    })
+                })?
+            })
+        }
+        Command::Cc { input, exact, .. } if *exact => {
+            input.repr(&[Repr::Csr])?;
+            with_csr!(input, &mut rng, |g| {
+                repeat_many(reps, || {
+                    let t = triangles(&g);
+                    Ok(vec![
+                        ("triangles", Value::Int(t.total())),
+                        ("transitivity", Value::Float(t.transitivity())),
+                        ("average-local", Value::Float(t.average_local())),
+                    ])
+                })?
+            })
+        }
+        Command::Cc { input, k, .. } => {
+            match input.repr(&[Repr::Csr, Repr::Adjacency, Repr::Compressed, Repr::Mapped])? {
+                Repr::Mapped => {
+                    let g = input.map()?;
+                    clustering(&g, *k, reps, &mut rng)?
+                }
+                Repr::Compressed => {
+                    let g: CompressedCSR = input.load(&mut rng)?;
+                    clustering(&g, *k, reps, &mut rng)?
+                }
+                Repr::Adjacency => {
+                    let g: Adjacency = input.load(&mut rng)?;
+                    clustering(&g, *k, reps, &mut rng)?
+                }
+                _ => with_csr!(input, &mut rng, |g| {
+                    clustering(&Sorted::new(g), *k, reps, &mut rng)?
+                }),
+            }
+        }
+        Command::Mincut {
+            input,
+            find_mode,
+            fast,
+        } => {
+            input.repr(&[Repr::EdgeList])?;
+            let g: EdgeList = input.load(&mut rng)?;
+            match find_mode {
+                FindModeKind::Compression => mincut::<PathCompression>(&g, *fast, reps),
+                FindModeKind::Halving => mincut::<PathHalving>(&g, *fast, reps),
+                FindModeKind::Splitting => mincut::<PathSplitting>(&g, *fast, reps),
+            }
+        }
+        Command::Components {
+            input,
+            strong,
+            check,
+        } => {
+            input.repr(&[Repr::Csr])?;
+            let mut last = None;
+            let records = with_csr!(input, &mut rng, |g| {
+                repeat_many(reps, || {
+                    let c = if *strong {
+                        components::strongly_connected(&g)
+                    } else {
+                        components::weakly_connected(&g)
+                    };
+                    let largest = c.largest().map_or(0, |l| c.sizes()[l]);
+                    let histogram = c.size_histogram().into_iter().collect();
+                    let count = c.count;
+                    last = Some(c);
+                    Ok(vec![
+                        ("components", Value::Int(count)),
+                        ("largest", Value::Int(largest)),
+                        ("sizes", Value::Histogram(histogram)),
+                    ])
+                })?
+            });
+            if let (Some(basename), Some(c)) = (check, &last) {
+                check_scc(c, basename)?;
+            }
+            records
+        }
+        Command::Generate { generator, output } => {
+            let (n, edges) = generator.generate(&mut rng);
+            return output.write(&<EdgeList as FromEdges>::from_edges(n, edges));
+        }
+        Command::Convert { input, output } => {
+            let g: EdgeList = input.load(&mut rng)?;
+            return output.write(&g);
+        }
+    };
+    print_records(opt.output, &records);
+    Ok(())
+}
+
 fn main() {
-    aava::main()
+    if let Err(e) = run(Opt::from_args()) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 }
@@ -1,10 +1,7 @@
 mod format_bit_slice;
 
 use format_bit_slice::format_slice;
-use std::{
-    fmt::{self, Debug},
-    mem::size_of,
-};
+use std::fmt::{self, Debug};
 // use std::ops::{Index, IndexMut};
 
 /// This vector represents an array of packed numbers
@@ -17,7 +14,7 @@ pub struct BitArray {
     capacity: usize,
 }
 
-const WORD_SIZE: usize = size_of::<u8>() * 8;
+const WORD_SIZE: usize = u8::BITS as usize;
 
 impl BitArray {
     pub fn new(register_size: u8, capacity: usize) -> Self {
@@ -27,7 +24,7 @@ impl BitArray {
             WORD_SIZE
         );
         let register_size_ = register_size as usize;
-        let slice_len = ((register_size_ * capacity) + (WORD_SIZE - 1)) / WORD_SIZE;
+        let slice_len = (register_size_ * capacity).div_ceil(WORD_SIZE);
         Self {
             register_size,
             elems: vec![0; slice_len].into(),
@@ -39,6 +36,10 @@ impl BitArray {
         self.capacity
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.capacity == 0
+    }
+
     pub fn register_size(&self) -> u8 {
         self.register_size
     }
@@ -90,7 +91,7 @@ impl BitArray {
 
     pub fn iter3(&self) -> Iter<'_> {
         Iter {
-            slice: &*self.elems,
+            slice: &self.elems,
             register_size: self.register_size,
             mask: init_left_mask(self.register_size as usize),
             count: self.capacity,
@@ -198,7 +199,7 @@ impl Iterator for Iter2<'_> {
 
 impl Debug for BitArray {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        format_slice(&*self.elems, self.register_size as usize, self.capacity, f)
+        format_slice(&self.elems, self.register_size as usize, self.capacity, f)
     }
 }
 
@@ -212,7 +213,7 @@ impl Clone for BitArray {
     }
 
     fn clone_from(&mut self, other: &Self) {
-        self.register_size = self.register_size;
+        self.register_size = other.register_size;
         self.elems.clone_from(&other.elems);
         self.capacity = other.capacity;
     }
@@ -262,6 +263,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn clone_from() {
+        let mut v = BitArray::new(5, 10);
+        (0..10).for_each(|n| v.set(n, n as u8 * 3));
+        let mut w = BitArray::new(2, 3);
+        w.clone_from(&v);
+        assert_eq!(w.register_size(), 5);
+        assert_eq!(w.len(), 10);
+        assert!(w.iter().eq(v.iter()));
+    }
+
     #[test]
     fn len() {
         for i in 1..16 {
@@ -8,7 +8,7 @@ pub fn format_slice(
     count: usize,
     f: &mut fmt::Formatter<'_>,
 ) -> fmt::Result {
-    let slice_len = ((r_size * count) + (WORD_SIZE - 1)) / WORD_SIZE;
+    let slice_len = (r_size * count).div_ceil(WORD_SIZE);
     writeln!(
         f,
         "[{}]",
@@ -333,12 +333,12 @@ mod tests {
         ds.union(2, 4);
         assert!(ds.are_connected(2, 4));
         ds.union(2, 3);
-        for i in (0..10).filter(|i| [2, 3, 4, 6].contains(&i)) {
-            for j in (0..10).filter(|i| [2, 3, 4, 6].contains(&i)) {
+        for i in (0..10).filter(|i| [2, 3, 4, 6].contains(i)) {
+            for j in (0..10).filter(|i| [2, 3, 4, 6].contains(i)) {
                 assert!(ds.are_connected(i, j), "{} <-> {}", i, j);
             }
         }
-        for i in (0..10).filter(|i| ![2, 3, 4, 6].contains(&i)) {
+        for i in (0..10).filter(|i| ![2, 3, 4, 6].contains(i)) {
             for &j in &[2, 3, 4, 6] {
                 if i != j {
                     assert!(!ds.are_connected(i, j), "{} <-> {}", i, j);
@@ -346,7 +346,7 @@ mod tests {
             }
         }
         for &i in &[2, 3, 4, 6] {
-            for j in (0..10).filter(|i| ![2, 3, 4, 6].contains(&i)) {
+            for j in (0..10).filter(|i| ![2, 3, 4, 6].contains(i)) {
                 if i != j {
                     assert!(!ds.are_connected(i, j), "{} <-> {}", i, j);
                 }
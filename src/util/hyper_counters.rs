@@ -100,7 +100,7 @@ mod tests {
     fn equivalence_after_union() {
         let seed = crate::util::random_numbs::random_seed();
         let mut h1s = vec![HyperLogLog::new_with_seed(B::B4, seed); 16];
-        let mut h2s = vec![CompactHyperLogLog::new_with_seed(B::B4, 10_000, seed)];
+        let mut h2s = [CompactHyperLogLog::new_with_seed(B::B4, 10_000, seed)];
         for i in 0..10_000 {
             let value = rand::random::<i32>();
             for (h1, h2) in h1s.iter_mut().zip(h2s.iter_mut()) {
@@ -5,7 +5,7 @@ use rustc_hash::FxHasher;
 use std::{
     convert::*,
     f64,
-    hash::{BuildHasher, BuildHasherDefault, Hash, Hasher},
+    hash::{BuildHasher, BuildHasherDefault, Hash},
     marker::PhantomData,
 };
 
@@ -84,9 +84,7 @@ where
     H: BuildHasher,
 {
     fn register(&mut self, v: T) {
-        let mut hasher = self.build_hasher.build_hasher();
-        v.hash(&mut hasher);
-        let x = jenkins(hasher.finish(), self.seed);
+        let x = jenkins(self.build_hasher.hash_one(v), self.seed);
         // The original algorithm specifies that the first b bits of the
         // hashed value are to be used for indexing. Because it's simpler to use
         // the last b bits instead, and the hash function is "random" anyway,
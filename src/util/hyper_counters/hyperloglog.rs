@@ -2,7 +2,7 @@ use super::{CounterArray, HyperLogLogCounter, B};
 use crate::util::{jenkins, random_numbs};
 use rustc_hash::FxHasher;
 use std::{
-    hash::{BuildHasher, BuildHasherDefault, Hash, Hasher},
+    hash::{BuildHasher, BuildHasherDefault, Hash},
     marker::PhantomData,
     ops::RangeInclusive,
 };
@@ -29,7 +29,7 @@ impl<T, H: Clone> Clone for HyperLogLog<T, H> {
         Self {
             registers: self.registers.clone(),
             hasher: self.hasher.clone(),
-            alpha_mm: self.alpha_mm.clone(),
+            alpha_mm: self.alpha_mm,
             ..*self
         }
     }
@@ -100,9 +100,7 @@ impl<T: Hash, H: BuildHasher> HyperLogLog<T, H> {
 
 impl<T: Hash, H: BuildHasher> HyperLogLogCounter<T> for HyperLogLog<T, H> {
     fn register(&mut self, v: T) {
-        let mut hasher = self.hasher.build_hasher();
-        v.hash(&mut hasher);
-        let x = jenkins(hasher.finish(), self.seed);
+        let x = jenkins(self.hasher.hash_one(v), self.seed);
         // The original algorithm specifies that the first b bits of the
         // hashed value are to be used for indexing. Because it's simpler to use
         // the last b bits instead, and the hash function is "random" anyway,
@@ -148,10 +146,10 @@ impl<T: Hash, H: BuildHasher> HyperLogLogCounter<T> for HyperLogLog<T, H> {
         self.registers
             .iter()
             .zip(other.registers.iter_mut())
-            .any(|(s, o)| {
+            .fold(false, |changed, (s, o)| {
                 let old_o = *o;
                 *o = u8::max(*s, *o);
-                old_o != *o
+                changed || old_o != *o
             })
     }
 }
@@ -194,6 +192,26 @@ mod tests {
         };
     }
 
+    #[test]
+    fn union_merges_every_register() {
+        let mut a = HyperLogLog::new_with_seed(B::B4, 0xBAD5EED);
+        let mut b = a.clone();
+        (0..50).for_each(|i| a.register(i));
+        (50..100).for_each(|i| b.register(i));
+        let (ra, rb) = (a.state(), b.state());
+        // the first register a changes isn't the only one
+        assert!(ra.iter().zip(rb.iter()).filter(|(x, y)| x > y).count() > 1);
+        assert!(ra.iter().zip(rb.iter()).any(|(x, y)| x < y));
+        let expected = ra
+            .iter()
+            .zip(rb.iter())
+            .map(|(&x, &y)| u8::max(x, y))
+            .collect::<Vec<_>>();
+        assert!(a.union_onto(&mut b));
+        assert_eq!(*b.state(), expected[..]);
+        assert!(!a.union_onto(&mut b));
+    }
+
     // make_test!(4, 5, 6, 7, 8);
     make_test!(9, 10, 11, 12, 13, 14, 15);
 